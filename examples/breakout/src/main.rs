@@ -79,7 +79,7 @@ impl EntHooks for Ball {
         let transform = ent.get::<Transform>()?;
         g.draw_rect(
             self.size,
-            transform.pos + viewport,
+            transform.pos - viewport,
             self.color,
             None,
             Some(transform.scale),
@@ -200,7 +200,7 @@ impl EntHooks for BrickHooks {
         let ent = w.get(ent)?;
         let t = ent.get::<Transform>()?;
         let color = ent.get::<Brick>()?.color;
-        g.draw_rect(t.size, t.pos + viewport, color, None, Some(t.scale), None);
+        g.draw_rect(t.size, t.pos - viewport, color, None, Some(t.scale), None);
         Ok(())
     }
 
@@ -292,7 +292,7 @@ impl EntHooks for PlayerHooks {
         let ent = w.get(ent)?;
         let t = ent.get::<Transform>()?;
         let p = ent.get::<Player>()?;
-        g.draw_rect(t.size, t.pos + viewport, p.color, None, Some(t.scale), None);
+        g.draw_rect(t.size, t.pos - viewport, p.color, None, Some(t.scale), None);
         Ok(())
     }
 
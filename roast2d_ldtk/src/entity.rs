@@ -0,0 +1,37 @@
+use roast2d::{
+    derive::{Component, Resource},
+    prelude::{hashbrown::HashMap, *},
+};
+
+/// LDtk entity instance info, attached to entities spawned by `load_level`
+#[derive(Component, Debug, Clone)]
+pub struct LdtkEntity {
+    /// Unique instance id
+    pub iid: String,
+    /// Entity definition identifier
+    pub identifier: String,
+}
+
+/// Index of entities spawned from the current level, keyed by LDtk iid
+#[derive(Resource, Default)]
+pub struct LdtkEntities {
+    by_iid: HashMap<String, Ent>,
+}
+
+impl LdtkEntities {
+    pub(crate) fn insert(&mut self, iid: String, ent: Ent) {
+        self.by_iid.insert(iid, ent);
+    }
+
+    /// Get entity by iid
+    pub fn get(&self, iid: &str) -> Option<Ent> {
+        self.by_iid.get(iid).cloned()
+    }
+}
+
+/// Find a spawned entity by LDtk iid, entity references in LDtk fields are iids
+pub fn find_by_iid(w: &World, iid: &str) -> Option<Ent> {
+    let ent = w.get_resource::<LdtkEntities>().ok()?.get(iid)?;
+    // the entity may be despawned
+    w.get(ent).is_ok().then_some(ent)
+}
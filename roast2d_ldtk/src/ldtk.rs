@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use anyhow::{anyhow, Result};
 use de::DeserializeOwned;
 use glam::Vec2;
@@ -5,15 +7,23 @@ use roast2d::prelude::*;
 use serde::*;
 use serde_json::Value;
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LdtkProject {
     pub json_version: String,
     pub defs: LdtkDefs,
     pub levels: Vec<LdtkLevel>,
+    /// Directory of the project file relative to the asset root, set by `LdtkLoader`
+    #[serde(skip)]
+    pub dir: PathBuf,
 }
 
 impl LdtkProject {
+    /// Path of a tileset relative to the asset root, tileset paths are relative to the project file
+    pub fn tileset_path(&self, rel_path: &str) -> PathBuf {
+        self.dir.join(rel_path)
+    }
+
     pub fn get_tileset(&self, uid: u32) -> Option<&LdtkTileset> {
         self.defs.tilesets.iter().find(|t| t.uid == uid)
     }
@@ -41,7 +51,7 @@ impl LdtkProject {
     }
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LdtkDefs {
     pub layers: Vec<LdtkLayer>,
@@ -51,7 +61,7 @@ pub struct LdtkDefs {
     pub level_fields: Vec<LdtkLevelField>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum LayerType {
     IntGrid,
     Entities,
@@ -59,7 +69,7 @@ pub enum LayerType {
     AutoLayer,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LdtkLayer {
     pub identifier: String,
@@ -68,7 +78,7 @@ pub struct LdtkLayer {
     pub grid_size: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LdtkEnt {
     pub identifier: String,
@@ -98,7 +108,7 @@ impl From<LdtkTileRect> for Rect {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LdtkTileset {
     pub identifier: String,
@@ -128,7 +138,7 @@ impl LdtkTileset {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LdtkEnum {
     pub identifier: String,
@@ -142,14 +152,14 @@ impl LdtkEnum {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LdtkEnumValue {
     pub id: String,
     pub tile_rect: Option<LdtkTileRect>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LdtkLevelField {
     pub identifier: String,
@@ -157,7 +167,7 @@ pub struct LdtkLevelField {
     pub r#type: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LdtkLevel {
     pub identifier: String,
@@ -218,7 +228,7 @@ pub struct LdtkFieldInstance {
     pub def_uid: u32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LdtkLevelLayerInstance {
     #[serde(rename = "__identifier")]
@@ -5,12 +5,14 @@ use roast2d::prelude::*;
 use roast2d_physics::collision_map::{CollisionMap, DefaultCollisionRule, COLLISION_MAP};
 
 use crate::{
+    entity::{LdtkEntities, LdtkEntity},
     ldtk::{LayerType, LdtkLevel, LdtkLevelLayerInstance, LdtkProject},
     map::BackgroundMaps,
 };
 
 pub(crate) const FOREGROUND: &str = "foreground";
 pub(crate) const DISTANCE: &str = "distance";
+pub(crate) const NAME: &str = "name";
 pub(crate) const DEFAULT_DISTANCE: f32 = 1.0;
 
 pub fn build_collision_map_from_ldtk_layer(layer: &LdtkLevelLayerInstance) -> Result<CollisionMap> {
@@ -101,6 +103,30 @@ pub fn build_map_from_ldtk_layer(
 }
 
 /// Load level
+///
+/// Spawned entities carry a `LdtkEntity` component, are tagged with the LDtk identifier
+/// and named by the `name` field if presented, use `find_by_iid` to lookup by iid.
+///
+/// Load the project by `LdtkLoader` and wait its load state to know when tilesets are ready,
+/// tilesets are resolved by `LdtkProject::tileset_path`, so they share the handles loaded as
+/// the project dependencies. The project is borrowed from the engine assets, clone it first.
+///
+/// # Examples
+///
+/// ```
+/// # use roast2d::prelude::*;
+/// # use roast2d_ldtk::{ldtk::LdtkProject, level::load_level};
+/// # fn spawn(w: &mut World, _id: &str, t: Transform, _v: serde_json::Value) -> Result<Ent> {
+/// #     Ok(w.spawn().add(t).id())
+/// # }
+/// # fn init(g: &mut Engine, w: &mut World, project: &Handle) -> Result<()> {
+///   if g.assets.load_state(project).is_loaded() {
+///       let proj = g.assets.get::<LdtkProject>(project).cloned().unwrap();
+///       load_level(g, w, &proj, "Level_0", spawn)?;
+///   }
+/// # Ok(())
+/// # }
+/// ```
 pub fn load_level<InitEntF: Fn(&mut World, &str, Transform, serde_json::Value) -> Result<Ent>>(
     g: &mut Engine,
     w: &mut World,
@@ -110,6 +136,7 @@ pub fn load_level<InitEntF: Fn(&mut World, &str, Transform, serde_json::Value) -
 ) -> Result<()> {
     let level = proj.get_level(identifier)?;
     let mut background_maps = BackgroundMaps::default();
+    let mut ldtk_entities = LdtkEntities::default();
     g.input.clear();
 
     for (index, layer) in level.layer_instances.iter().enumerate() {
@@ -120,7 +147,7 @@ pub fn load_level<InitEntF: Fn(&mut World, &str, Transform, serde_json::Value) -
             }
             LayerType::AutoLayer | LayerType::Tiles => {
                 let tileset = if let Some(rel_path) = layer.tileset_rel_path.as_ref() {
-                    g.assets.load_texture(rel_path)
+                    g.assets.load_texture(proj.tileset_path(rel_path))
                 } else {
                     bail!(
                         "Layer {}-{} doesn't has tileset",
@@ -146,7 +173,22 @@ pub fn load_level<InitEntF: Fn(&mut World, &str, Transform, serde_json::Value) -
                         .collect();
                     let transform =
                         Transform::new(pos, Vec2::new(ent_ins.width as f32, ent_ins.height as f32));
-                    init_ent_func(w, identifier, transform, settings)?;
+                    let ent = init_ent_func(w, identifier, transform, settings)?;
+
+                    // record instance info, so entities can be found by name, tag or iid
+                    let mut ent_mut = w.get_mut(ent)?;
+                    ent_mut
+                        .add(LdtkEntity {
+                            iid: ent_ins.iid.clone(),
+                            identifier: identifier.clone(),
+                        })
+                        .tag(identifier.as_str());
+                    if ent_mut.get::<Name>().is_err() {
+                        if let Some(name) = ent_ins.get(NAME).and_then(|v| v.as_str()) {
+                            ent_mut.add(Name::new(name));
+                        }
+                    }
+                    ldtk_entities.insert(ent_ins.iid.clone(), ent);
                 }
             }
             _ => {
@@ -154,6 +196,8 @@ pub fn load_level<InitEntF: Fn(&mut World, &str, Transform, serde_json::Value) -
             }
         }
     }
+    w.add_resource(background_maps);
+    w.add_resource(ldtk_entities);
 
     Ok(())
 }
@@ -1,3 +1,5 @@
+pub mod entity;
 pub mod ldtk;
 pub mod level;
+pub mod loader;
 pub mod map;
@@ -0,0 +1,138 @@
+use std::path::Path;
+
+use roast2d::{
+    asset::{AssetType, Dependency},
+    prelude::*,
+};
+
+use crate::ldtk::LdtkProject;
+
+/// Load `.ldtk` project files through `AssetManager`
+///
+/// Tilesets are loaded as dependencies, the project handle is loaded once all tilesets are ready.
+pub struct LdtkLoader;
+
+impl AssetLoader for LdtkLoader {
+    type Asset = LdtkProject;
+
+    fn extensions(&self) -> &[&str] {
+        &["ldtk"]
+    }
+
+    fn load(&self, bytes: Vec<u8>, path: &Path) -> Result<LdtkProject> {
+        let mut project: LdtkProject = serde_json::from_slice(&bytes)?;
+        project.dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(project)
+    }
+
+    fn dependencies(&self, project: &LdtkProject, _path: &Path) -> Vec<Dependency> {
+        project
+            .defs
+            .tilesets
+            .iter()
+            .filter_map(|tileset| tileset.rel_path.as_ref())
+            .map(|rel_path| (project.tileset_path(rel_path), AssetType::Texture))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::Path,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use roast2d::{asset::EmbeddedAssetReader, prelude::*};
+
+    use super::LdtkLoader;
+    use crate::{entity::find_by_iid, ldtk::LdtkProject, level::load_level, map::BackgroundMaps};
+
+    const PROJECT: &str = r#"{
+        "jsonVersion": "1.5.3",
+        "defs": {
+            "layers": [],
+            "entities": [],
+            "tilesets": [{
+                "identifier": "Tiles", "uid": 1, "relPath": "tiles.png",
+                "pxWid": 16, "pxHei": 16, "tileGridSize": 8,
+                "tagSourceEnumUid": null, "spacing": 0, "padding": 0
+            }],
+            "enums": [],
+            "levelFields": []
+        },
+        "levels": [{
+            "identifier": "Level_0", "iid": "level-0", "uid": 0,
+            "bgRelPath": null, "bgColor": null, "fieldInstances": [],
+            "layerInstances": [{
+                "__identifier": "Ground", "__type": "Tiles",
+                "__cWid": 2, "__cHei": 2, "__gridSize": 8,
+                "__tilesetDefUid": 1, "__tilesetRelPath": "tiles.png",
+                "iid": "ground", "levelId": 0, "layerDefUid": 2,
+                "entityInstances": [], "autoLayerTiles": [], "intGridCsv": [],
+                "gridTiles": [{ "px": [0, 0], "src": [8, 0], "f": 0, "t": 1, "d": [0], "a": 1 }]
+            }, {
+                "__identifier": "Entities", "__type": "Entities",
+                "__cWid": 2, "__cHei": 2, "__gridSize": 8,
+                "__tilesetDefUid": null, "__tilesetRelPath": null,
+                "iid": "entities", "levelId": 0, "layerDefUid": 3,
+                "autoLayerTiles": [], "gridTiles": [], "intGridCsv": [],
+                "entityInstances": [{
+                    "__identifier": "Player", "iid": "player", "__grid": [1, 1],
+                    "__pivot": [0.5, 0.5], "__tile": null, "width": 8, "height": 8,
+                    "defUid": 4, "px": [8, 8], "fieldInstances": []
+                }]
+            }]
+        }]
+    }"#;
+
+    /// Encode a png with the size
+    fn png(size: u32) -> &'static [u8] {
+        let mut bytes = Vec::new();
+        image::RgbaImage::new(size, size)
+            .write_to(
+                &mut std::io::Cursor::new(&mut bytes),
+                image::ImageFormat::Png,
+            )
+            .unwrap();
+        bytes.leak()
+    }
+
+    #[test]
+    fn test_load_level_from_loader() {
+        let mut g = Engine::headless();
+        g.assets.set_reader(
+            EmbeddedAssetReader::empty()
+                .embed("maps/world.ldtk", PROJECT.as_bytes())
+                .embed("maps/tiles.png", png(16)),
+        );
+        g.assets.register_loader(LdtkLoader);
+        let project = g.assets.load_asset("maps/world.ldtk");
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while g.assets.load_state(&project) == LoadState::Pending {
+            assert!(Instant::now() < deadline, "Timeout loading project");
+            pollster::block_on(g.handle_assets()).unwrap();
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!(g.assets.load_state(&project).is_loaded());
+
+        let proj = g.assets.get::<LdtkProject>(&project).cloned().unwrap();
+        assert_eq!(proj.dir, Path::new("maps"));
+        let mut w = World::default();
+        load_level(&mut g, &mut w, &proj, "Level_0", |w, _id, t, _v| {
+            Ok(w.spawn().add(t).id())
+        })
+        .unwrap();
+
+        // the tileset is the handle loaded as the project dependency
+        let maps = w.get_resource::<BackgroundMaps>().unwrap();
+        assert_eq!(maps.maps.len(), 1);
+        assert_eq!(
+            &maps.maps[0].tileset.texture,
+            &g.assets.dependencies(&project)[0]
+        );
+        assert!(find_by_iid(&w, "player").is_some());
+    }
+}
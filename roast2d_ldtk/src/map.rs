@@ -18,11 +18,13 @@ pub fn draw_maps(g: &mut Engine, w: &mut World, foreground: bool) {
     let viewport = g.viewport();
 
     if let Ok(background_maps) = w.get_resource::<BackgroundMaps>() {
-        for map in background_maps.maps.iter().rev() {
-            if map.foreground == foreground {
-                draw_map_tiles(g, map, viewport);
+        g.draw_world(|g| {
+            for map in background_maps.maps.iter().rev() {
+                if map.foreground == foreground {
+                    draw_map_tiles(g, map, viewport);
+                }
             }
-        }
+        });
     }
 }
 
@@ -40,7 +42,8 @@ fn draw_tile(g: &mut Engine, map: &Map, tile: &Tile, pos: Vec2) {
 }
 
 pub(crate) fn draw_map_tiles(g: &mut Engine, map: &Map, mut offset: Vec2) {
-    offset /= map.distance;
+    // the current layer may already apply the camera
+    offset = offset / map.distance - g.layer_offset();
     let half_tile_size = map.tile_size * 0.5;
 
     for tile in map.data.iter() {
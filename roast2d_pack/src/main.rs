@@ -0,0 +1,17 @@
+use std::{env, fs::File, io::BufWriter, process};
+
+use roast2d::{asset::pack::PackBuilder, prelude::*};
+
+fn main() -> Result<()> {
+    let args: Vec<String> = env::args().collect();
+    let [_, dir, output] = args.as_slice() else {
+        eprintln!("Usage: roast2d_pack <assets dir> <output pack>");
+        process::exit(1);
+    };
+    let mut builder = PackBuilder::new();
+    builder.add_dir(dir)?;
+    let file = File::create(output)?;
+    builder.write(BufWriter::new(file))?;
+    println!("Packed {} files into {output}", builder.len());
+    Ok(())
+}
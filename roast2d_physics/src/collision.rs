@@ -119,6 +119,7 @@ pub(crate) fn update_collision(g: &mut Engine, w: &mut World) {
                 if sweep_axis.get(ent2_bounds.min) > max_pos {
                     break;
                 }
+                g.perf.physics_pair_checks += 1;
                 if let Some(overlap) = calc_ent_overlap(w, ent1, ent2) {
                     let res = {
                         let [ent1, ent2] = w.many([ent1, ent2]);
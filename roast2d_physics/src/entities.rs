@@ -12,7 +12,8 @@ use anyhow::Result;
 use glam::Vec2;
 
 pub fn draw_entities(g: &mut Engine, w: &mut World) {
-    let viewport = g.viewport();
+    // the current layer may already apply the camera
+    let viewport = g.viewport() - g.layer_offset();
     // Sort entities by draw_order
     let mut ents: Vec<_> = w
         .iter_ents_ref()
@@ -22,16 +23,51 @@ pub fn draw_entities(g: &mut Engine, w: &mut World) {
         })
         .collect();
     ents.sort_by_key(|(_ent, z)| *z);
-    for (ent, _z) in ents {
-        if let Ok(hooks) = get_ent_hooks(w, ent) {
-            if let Err(err) = hooks.draw(g, w, ent, viewport) {
-                log::error!("Error occured when call draw hooks on {ent:?}: {err}");
+    g.draw_world(|g| {
+        for (ent, _z) in ents {
+            if let Ok(hooks) = get_ent_hooks(w, ent) {
+                if let Err(err) = hooks.draw(g, w, ent, viewport) {
+                    log::error!("Error occured when call draw hooks on {ent:?}: {err}");
+                }
             }
         }
-    }
+    });
 }
 
 pub fn update_entities(g: &mut Engine, w: &mut World) {
+    g.measure("entities", |g| update_entities_hooks(g, w));
+    g.measure("collision", |g| collision::update_collision(g, w));
+    kill_expired(w);
+    handle_commands(g, w);
+}
+
+/// Kill entities with expired lifetime through commands, so `EntHooks::kill` is called.
+/// Entities without hooks are left to the engine.
+fn kill_expired(w: &mut World) {
+    let ents: Vec<_> = w
+        .iter_by_id(&ComponentId::of::<Lifetime>())
+        .cloned()
+        .collect();
+    let expired: Vec<_> = ents
+        .into_iter()
+        .filter(|ent| {
+            let ent_ref = w.ent(*ent);
+            ent_ref.get::<Hooks>().is_ok()
+                && ent_ref.get::<Lifetime>().is_ok_and(|l| l.is_expired())
+        })
+        .collect();
+    if expired.is_empty() {
+        return;
+    }
+    let Ok(commands) = w.get_resource_mut::<Commands>() else {
+        return;
+    };
+    for ent in expired {
+        commands.kill(ent);
+    }
+}
+
+fn update_entities_hooks(g: &mut Engine, w: &mut World) {
     // Update all entities
     let ents: Vec<_> = w.iter_ents().cloned().collect();
     for ent in ents {
@@ -49,9 +85,6 @@ pub fn update_entities(g: &mut Engine, w: &mut World) {
             }
         }
     }
-
-    collision::update_collision(g, w);
-    handle_commands(g, w);
 }
 
 /// Init commands
@@ -189,7 +222,7 @@ pub trait EntHooks {
         Ok(())
     }
 
-    // Draw entity anim
+    // Draw entity anim, subtract viewport from world positions
     fn draw(&self, g: &mut Engine, w: &mut World, ent: Ent, viewport: Vec2) -> Result<()> {
         let ent = w.get(ent)?;
         let sprite = ent.get::<Sprite>()?;
@@ -281,3 +314,39 @@ pub(crate) fn get_ent_hooks(w: &mut World, ent: Ent) -> Result<Rc<dyn EntHooks>,
     w.get(ent)
         .and_then(|ent_ref| ent_ref.get::<Hooks>().map(|h| h.get()))
 }
+
+#[cfg(test)]
+mod tests {
+    use roast2d::prelude::*;
+
+    use super::{kill_expired, Command, Commands, EntHooks, Hooks};
+
+    struct Bullet;
+
+    impl EntHooks for Bullet {}
+
+    #[test]
+    fn test_kill_expired() {
+        let mut w = World::default();
+        w.add_resource(Commands::default());
+        let bullet = w
+            .spawn()
+            .add(Hooks::new(Bullet))
+            .add(Lifetime::new(0.0))
+            .id();
+        let alive = w
+            .spawn()
+            .add(Hooks::new(Bullet))
+            .add(Lifetime::new(1.0))
+            .id();
+        let plain = w.spawn().add(Lifetime::new(0.0)).id();
+
+        kill_expired(&mut w);
+        // entities are killed by commands, entities without hooks are left to the engine
+        let commands = w.get_resource_mut::<Commands>().unwrap().take();
+        assert!(matches!(commands.as_slice(), [Command::KillEnt { ent }] if *ent == bullet));
+        assert!(w.get(bullet).is_ok());
+        assert!(w.get(alive).is_ok());
+        assert!(w.get(plain).is_ok());
+    }
+}
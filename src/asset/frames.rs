@@ -0,0 +1,145 @@
+//! Decode animated images into a frame sheet
+
+use std::{io::Cursor, path::Path};
+
+use anyhow::Result;
+use glam::UVec2;
+use image::{
+    codecs::{gif::GifDecoder, png::PngDecoder},
+    AnimationDecoder, Frame, ImageDecoder, ImageFormat,
+};
+
+use crate::{
+    sprite::{SpriteFrame, SpriteFrames},
+    types::Rect,
+};
+
+/// Duration of frames without delay, as browsers do
+const DEFAULT_FRAME_DURATION: f32 = 0.1;
+
+/// Decoded frames packed into a grid
+pub(crate) struct FrameSheet {
+    pub data: Vec<u8>,
+    pub size: UVec2,
+    pub frames: SpriteFrames,
+}
+
+/// Decode all frames of GIF and APNG, other images are decoded as a single frame
+pub(crate) fn decode_frames(path: &Path, bytes: &[u8]) -> Result<FrameSheet> {
+    let format = ImageFormat::from_path(path).or_else(|_| image::guess_format(bytes))?;
+    let (frame_size, frames) = match format {
+        ImageFormat::Gif => {
+            let decoder = GifDecoder::new(Cursor::new(bytes))?;
+            let (w, h) = decoder.dimensions();
+            (UVec2::new(w, h), decoder.into_frames().collect_frames()?)
+        }
+        ImageFormat::Png if PngDecoder::new(Cursor::new(bytes))?.is_apng()? => {
+            let decoder = PngDecoder::new(Cursor::new(bytes))?;
+            let (w, h) = decoder.dimensions();
+            (
+                UVec2::new(w, h),
+                decoder.apng()?.into_frames().collect_frames()?,
+            )
+        }
+        _ => {
+            let im = image::load_from_memory_with_format(bytes, format)?.into_rgba8();
+            let size = UVec2::new(im.width(), im.height());
+            (size, vec![Frame::new(im)])
+        }
+    };
+    Ok(pack_frames(frame_size, frames))
+}
+
+/// Pack frames into a grid, frames are placed row by row
+fn pack_frames(frame_size: UVec2, frames: Vec<Frame>) -> FrameSheet {
+    let count = frames.len().max(1) as u32;
+    let columns = (count as f32).sqrt().ceil() as u32;
+    let rows = count.div_ceil(columns);
+    let size = UVec2::new(frame_size.x * columns, frame_size.y * rows);
+    let mut sheet = image::RgbaImage::new(size.x, size.y);
+
+    let mut sprite_frames = Vec::with_capacity(frames.len());
+    for (i, frame) in frames.into_iter().enumerate() {
+        let cell = UVec2::new(i as u32 % columns, i as u32 / columns) * frame_size;
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        let duration = if numer == 0 {
+            DEFAULT_FRAME_DURATION
+        } else {
+            numer as f32 / denom as f32 / 1000.0
+        };
+        let (left, top) = (frame.left(), frame.top());
+        let buffer = frame.into_buffer();
+        for (x, y, pixel) in buffer.enumerate_pixels() {
+            let (x, y) = (left + x, top + y);
+            if x < frame_size.x && y < frame_size.y {
+                sheet.put_pixel(cell.x + x, cell.y + y, *pixel);
+            }
+        }
+        let min = cell.as_vec2();
+        sprite_frames.push(SpriteFrame {
+            src: Rect {
+                min,
+                max: min + frame_size.as_vec2(),
+            },
+            duration,
+        });
+    }
+    FrameSheet {
+        data: sheet.into_raw(),
+        size,
+        frames: SpriteFrames {
+            frame_size,
+            frames: sprite_frames,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use glam::{UVec2, Vec2};
+    use image::{codecs::gif::GifEncoder, Delay, Frame, Rgba, RgbaImage};
+
+    use super::decode_frames;
+
+    #[test]
+    fn test_decode_gif() {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut bytes);
+            let frames = [
+                Rgba([255, 0, 0, 255]),
+                Rgba([0, 255, 0, 255]),
+                Rgba([0, 0, 255, 255]),
+            ]
+            .into_iter()
+            .map(|color| {
+                Frame::from_parts(
+                    RgbaImage::from_pixel(4, 2, color),
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(50, 1),
+                )
+            });
+            encoder.encode_frames(frames).unwrap();
+        }
+        let sheet = decode_frames(Path::new("effect.gif"), &bytes).unwrap();
+        let frames = &sheet.frames;
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames.frame_size, UVec2::new(4, 2));
+        // 2x2 grid
+        assert_eq!(sheet.size, UVec2::new(8, 4));
+        assert_eq!(frames.frames[2].src.min, Vec2::new(0.0, 2.0));
+        assert!((frames.frames[0].duration - 0.05).abs() < 1e-6);
+        // second frame is green
+        let px = |x: u32, y: u32| {
+            let i = ((y * sheet.size.x + x) * 4) as usize;
+            &sheet.data[i..i + 4]
+        };
+        assert_eq!(px(4, 0), &[0, 255, 0, 255]);
+        assert_eq!(frames.frame_at(0.06), 1);
+        // loop
+        assert_eq!(frames.frame_at(0.16), 0);
+    }
+}
@@ -0,0 +1,100 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{anyhow, Result};
+use hashbrown::HashMap;
+
+use super::{get_default_reader, AssetReader, ErasedAssetReader};
+
+/// Serve bytes embedded in the binary, read from the fallback reader if the path is not embedded
+///
+/// Use `embedded_assets!` to embed files with `include_bytes!`
+pub struct EmbeddedAssetReader {
+    files: HashMap<String, &'static [u8]>,
+    fallback: Option<Box<dyn ErasedAssetReader>>,
+}
+
+impl EmbeddedAssetReader {
+    /// A reader without fallback, reads of not embedded paths fail
+    pub fn empty() -> Self {
+        Self {
+            files: Default::default(),
+            fallback: None,
+        }
+    }
+
+    /// Set the fallback reader
+    pub fn with_fallback<R: ErasedAssetReader>(mut self, reader: R) -> Self {
+        self.fallback = Some(Box::new(reader));
+        self
+    }
+
+    /// Embed bytes under a virtual path
+    pub fn embed(mut self, path: &str, bytes: &'static [u8]) -> Self {
+        self.insert(path, bytes);
+        self
+    }
+
+    pub fn insert(&mut self, path: &str, bytes: &'static [u8]) {
+        self.files.insert(path.replace('\\', "/"), bytes);
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.files.contains_key(&path.replace('\\', "/"))
+    }
+}
+
+impl AssetReader for EmbeddedAssetReader {
+    /// Fallback to the default reader of the path
+    fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            files: Default::default(),
+            fallback: Some(get_default_reader(path)),
+        }
+    }
+
+    fn get_full_path(&self, path: &str) -> PathBuf {
+        PathBuf::from(path)
+    }
+
+    async fn read<'a>(&'a self, path: &'a str) -> Result<Vec<u8>> {
+        if let Some(bytes) = self.files.get(&path.replace('\\', "/")) {
+            return Ok(bytes.to_vec());
+        }
+        match self.fallback.as_ref() {
+            Some(fallback) => fallback.read(path).await,
+            None => Err(anyhow!("Not found embedded asset {path}")),
+        }
+    }
+
+    fn modified(&self, path: &str) -> Option<SystemTime> {
+        if self.contains(path) {
+            return None;
+        }
+        self.fallback.as_ref()?.modified(path)
+    }
+}
+
+/// Create an `EmbeddedAssetReader` with files under the asset root embedded,
+/// paths are relative to the crate manifest dir, not embedded paths are read from the root
+///
+/// ```ignore
+/// let reader = embedded_assets!("assets", ["player.png", "font.ttf"]);
+/// g.assets.set_reader(reader);
+/// ```
+#[macro_export]
+macro_rules! embedded_assets {
+    ($root:literal, [$($path:literal),* $(,)?]) => {{
+        use $crate::asset::AssetReader as _;
+        let mut reader = $crate::asset::EmbeddedAssetReader::new($root);
+        $(
+            reader.insert(
+                $path,
+                include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $root, "/", $path)),
+            );
+        )*
+        reader
+    }};
+}
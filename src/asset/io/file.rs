@@ -2,9 +2,10 @@ use super::AssetReader;
 use std::{
     env, fs,
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 /// Copy from bevy <https://github.com/bevyengine/bevy/blob/99ab0285e459753838d0e0716fda9be7b4976a4c/crates/bevy_asset/src/io/file/mod.rs#L18>
 fn get_base_path() -> PathBuf {
@@ -34,7 +35,15 @@ impl AssetReader for FileAssetReader {
     }
 
     async fn read<'a>(&'a self, path: &'a str) -> Result<Vec<u8>> {
-        let b: Vec<_> = fs::read(self.get_full_path(path)).unwrap();
+        let full_path = self.get_full_path(path);
+        let b: Vec<_> = fs::read(&full_path)
+            .with_context(|| format!("Failed to read {}", full_path.display()))?;
         Ok(b)
     }
+
+    fn modified(&self, path: &str) -> Option<SystemTime> {
+        fs::metadata(self.get_full_path(path))
+            .and_then(|m| m.modified())
+            .ok()
+    }
 }
@@ -2,21 +2,32 @@ use std::{
     future::Future,
     path::{Path, PathBuf},
     pin::Pin,
+    time::SystemTime,
 };
 
 use anyhow::Result;
 
+mod embedded;
 #[cfg(not(target_arch = "wasm32"))]
 mod file;
+mod pack;
 #[cfg(target_arch = "wasm32")]
 mod web;
 
+pub use embedded::EmbeddedAssetReader;
+pub use pack::PackAssetReader;
+
+#[allow(async_fn_in_trait)]
 pub trait AssetReader: Send + Sync + 'static {
     fn new<P: AsRef<Path>>(path: P) -> Self
     where
         Self: Sized;
     fn get_full_path(&self, path: &str) -> PathBuf;
     async fn read<'a>(&'a self, path: &'a str) -> Result<Vec<u8>>;
+    /// Last modification time of the file, used to detect changes for hot reloading
+    fn modified(&self, _path: &str) -> Option<SystemTime> {
+        None
+    }
 }
 
 pub type BoxedFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
@@ -26,6 +37,8 @@ pub type BoxedFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
 pub trait ErasedAssetReader: Send + Sync + 'static {
     /// Returns a future to load the full file data at the provided path.
     fn read<'a>(&'a self, path: &'a str) -> BoxedFuture<'a, Result<Vec<u8>>>;
+    /// Last modification time of the file
+    fn modified(&self, path: &str) -> Option<SystemTime>;
 }
 
 impl<T: AssetReader> ErasedAssetReader for T {
@@ -35,6 +48,10 @@ impl<T: AssetReader> ErasedAssetReader for T {
             Ok(buf)
         })
     }
+
+    fn modified(&self, path: &str) -> Option<SystemTime> {
+        AssetReader::modified(self, path)
+    }
 }
 
 pub(crate) fn get_default_reader<P: AsRef<Path>>(path: P) -> Box<dyn ErasedAssetReader> {
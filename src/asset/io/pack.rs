@@ -0,0 +1,123 @@
+use std::{
+    future::poll_fn,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    task::{Poll, Waker},
+};
+
+use anyhow::{anyhow, Context, Result};
+
+use super::{get_default_reader, AssetReader, ErasedAssetReader};
+use crate::asset::pack::Pack;
+
+/// Serve reads from a pack, the pack file is fetched by the inner reader on first read
+pub struct PackAssetReader {
+    inner: Box<dyn ErasedAssetReader>,
+    pack_path: String,
+    /// The loaded pack or the error of loading, a failed pack is not fetched again
+    pack: OnceLock<Result<Pack, String>>,
+    /// Wakers of reads waiting for the pack, `Some` while a read is fetching the pack
+    loading: Mutex<Option<Vec<Waker>>>,
+}
+
+impl PackAssetReader {
+    /// Read the pack file with another reader
+    pub fn with_reader<R: ErasedAssetReader>(reader: R, pack_path: &str) -> Self {
+        Self {
+            inner: Box::new(reader),
+            pack_path: pack_path.to_owned(),
+            pack: OnceLock::new(),
+            loading: Mutex::new(None),
+        }
+    }
+
+    /// Serve reads from a loaded pack
+    pub fn from_pack(pack: Pack) -> Self {
+        let mut reader = Self::new("");
+        reader.pack = OnceLock::from(Ok(pack));
+        reader
+    }
+
+    /// Fetch the pack once, concurrent reads wait for the first one
+    async fn pack(&self) -> Result<&Pack> {
+        loop {
+            if let Some(pack) = self.pack.get() {
+                return pack.as_ref().map_err(|err| anyhow!("{err}"));
+            }
+            let fetch = {
+                let mut loading = self.loading.lock().unwrap();
+                // the pack may be loaded after the check above
+                if loading.is_none() && self.pack.get().is_none() {
+                    *loading = Some(Vec::new());
+                    true
+                } else {
+                    false
+                }
+            };
+            if fetch {
+                let guard = LoadingGuard(self);
+                let result = self.load().await.map_err(|err| format!("{err:#}"));
+                let _ = self.pack.set(result);
+                drop(guard);
+                continue;
+            }
+            poll_fn(|cx| {
+                let mut loading = self.loading.lock().unwrap();
+                match loading.as_mut() {
+                    Some(wakers) if self.pack.get().is_none() => {
+                        wakers.push(cx.waker().clone());
+                        Poll::Pending
+                    }
+                    _ => Poll::Ready(()),
+                }
+            })
+            .await;
+        }
+    }
+
+    async fn load(&self) -> Result<Pack> {
+        let bytes = self.inner.read(&self.pack_path).await?;
+        Pack::from_bytes(bytes).with_context(|| format!("Failed to load pack {}", self.pack_path))
+    }
+}
+
+/// Wake the waiting reads when the fetching read finishes or is dropped,
+/// if it is dropped before the pack is loaded a waiting read fetches it
+struct LoadingGuard<'a>(&'a PackAssetReader);
+
+impl Drop for LoadingGuard<'_> {
+    fn drop(&mut self) {
+        let wakers = self
+            .0
+            .loading
+            .lock()
+            .map(|mut l| l.take())
+            .unwrap_or_default();
+        for waker in wakers.into_iter().flatten() {
+            waker.wake();
+        }
+    }
+}
+
+impl AssetReader for PackAssetReader {
+    /// Path of the pack file relative to the base path
+    fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            inner: get_default_reader(""),
+            pack_path: path.as_ref().to_string_lossy().into_owned(),
+            pack: OnceLock::new(),
+            loading: Mutex::new(None),
+        }
+    }
+
+    fn get_full_path(&self, path: &str) -> PathBuf {
+        PathBuf::from(&self.pack_path).join(path)
+    }
+
+    async fn read<'a>(&'a self, path: &'a str) -> Result<Vec<u8>> {
+        let pack = self.pack().await?;
+        pack.get(path)
+            .map(ToOwned::to_owned)
+            .ok_or_else(|| anyhow!("Not found {path} in pack {}", self.pack_path))
+    }
+}
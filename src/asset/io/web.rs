@@ -78,7 +78,12 @@ impl AssetReader for WebAssetReader {
             .map_err(js_value_to_err("convert fetch to Response"))?;
         match resp.status() {
             200 => {
-                let data = JsFuture::from(resp.array_buffer().unwrap()).await.unwrap();
+                let data = JsFuture::from(
+                    resp.array_buffer()
+                        .map_err(js_value_to_err("read array buffer"))?,
+                )
+                .await
+                .map_err(js_value_to_err("read array buffer"))?;
                 let bytes = Uint8Array::new(&data).to_vec();
                 Ok(bytes)
             }
@@ -0,0 +1,73 @@
+use std::{
+    any::Any,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+
+use super::AssetType;
+
+/// Asset referenced by another asset
+pub type Dependency = (PathBuf, AssetType);
+
+/// Loaded asset with its dependencies
+pub(crate) type Loaded = (Box<dyn Any + Send + Sync>, Vec<Dependency>);
+
+/// Loader that turns fetched bytes into a typed asset,
+/// loaders are registered to `AssetManager` by file extension
+pub trait AssetLoader: Send + Sync + 'static {
+    type Asset: Send + Sync + 'static;
+
+    /// File extensions handled by the loader, without the leading dot
+    fn extensions(&self) -> &[&str];
+
+    fn load(&self, bytes: Vec<u8>, path: &Path) -> Result<Self::Asset>;
+
+    /// Assets referenced by the loaded asset, paths are relative to the asset root.
+    /// Dependencies are loaded along with the asset and released when it drops
+    fn dependencies(&self, _asset: &Self::Asset, _path: &Path) -> Vec<Dependency> {
+        Vec::new()
+    }
+}
+
+/// Object safe version of AssetLoader
+pub(crate) trait ErasedAssetLoader: Send + Sync + 'static {
+    fn load(&self, bytes: Vec<u8>, path: &Path) -> Result<Loaded>;
+}
+
+impl<T: AssetLoader> ErasedAssetLoader for T {
+    fn load(&self, bytes: Vec<u8>, path: &Path) -> Result<Loaded> {
+        let asset = AssetLoader::load(self, bytes, path)?;
+        let dependencies = self.dependencies(&asset, path);
+        Ok((Box::new(asset), dependencies))
+    }
+}
+
+/// Load json files into T
+pub struct JsonLoader<T> {
+    extensions: &'static [&'static str],
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> JsonLoader<T> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: DeserializeOwned + Send + Sync + 'static> AssetLoader for JsonLoader<T> {
+    type Asset = T;
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+
+    fn load(&self, bytes: Vec<u8>, _path: &Path) -> Result<T> {
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+}
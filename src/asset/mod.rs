@@ -1,32 +1,91 @@
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use std::{
+    any::Any,
+    collections::VecDeque,
     mem,
     path::{Path, PathBuf},
-    sync::mpsc::{channel, Receiver, Sender},
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc, Weak,
+    },
+    time::SystemTime,
 };
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
+use frames::FrameSheet;
 use glam::UVec2;
-use io::{get_default_reader, ErasedAssetReader};
+use io::get_default_reader;
+use loader::ErasedAssetLoader;
+#[cfg(not(target_arch = "wasm32"))]
+use task::WorkerPool;
+#[cfg(target_arch = "wasm32")]
+use task::{join_all, run_job};
+use task::{Decoded, Job, JobResult};
 
 use crate::{
     font::Font,
-    handle::{DropEvent, Handle, HandleId},
+    handle::{DropEvent, Handle, HandleId, StrongHandle},
 };
 
+mod frames;
 mod io;
+mod loader;
+pub mod pack;
+mod task;
+
+pub use io::{AssetReader, BoxedFuture, EmbeddedAssetReader, ErasedAssetReader, PackAssetReader};
+pub use loader::{AssetLoader, Dependency, JsonLoader};
+
+/// Interval in seconds to check watched files
+const HOT_RELOAD_INTERVAL: f32 = 1.0;
+/// Default bytes of textures uploaded per frame
+const DEFAULT_UPLOAD_BUDGET: usize = 16 * 1024 * 1024;
+
+/// Load state of an asset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadState {
+    /// Waiting to be fetched
+    Pending,
+    Loaded,
+    /// Failed to load, with the error message
+    Failed(String),
+}
+
+impl LoadState {
+    pub fn is_loaded(&self) -> bool {
+        matches!(self, Self::Loaded)
+    }
 
-#[derive(Debug)]
+    pub fn is_failed(&self) -> bool {
+        matches!(self, Self::Failed(_))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum AssetType {
     Raw,
     Texture,
     Font,
+    /// Texture of all frames of an animated image, with `SpriteFrames`
+    Frames,
+    /// Loaded by a registered `AssetLoader`
+    Custom,
 }
 
 pub(crate) struct PendingTask {
     pub handle: Handle,
     pub asset_type: AssetType,
     pub path: PathBuf,
+    /// Reload an already loaded asset
+    pub reload: bool,
+}
+
+/// A loaded file watched for hot reloading
+struct WatchedAsset {
+    handle: Weak<StrongHandle>,
+    asset_type: AssetType,
+    path: PathBuf,
+    modified: SystemTime,
 }
 
 pub(crate) enum FetchedTask {
@@ -52,25 +111,97 @@ pub struct Asset {
     pub bytes: Option<Vec<u8>>,
 }
 
+/// Memory held by assets in bytes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryStats {
+    pub textures: usize,
+    pub fonts: usize,
+    pub raw: usize,
+    pub custom: usize,
+    /// Textures evicted by the memory budget
+    pub evicted: usize,
+}
+
+impl MemoryStats {
+    pub fn total(&self) -> usize {
+        self.textures + self.fonts + self.raw + self.custom
+    }
+}
+
 pub struct AssetManager {
-    reader: Box<dyn ErasedAssetReader>,
+    reader: Arc<dyn ErasedAssetReader>,
     pending: Vec<PendingTask>,
+    job_id: u64,
+    /// Jobs being read and decoded
+    in_flight: HashMap<u64, PendingTask>,
+    /// Decoded jobs waiting for upload
+    ready: VecDeque<JobResult>,
+    upload_budget: usize,
+    #[cfg(not(target_arch = "wasm32"))]
+    workers: Option<WorkerPool>,
     asset_id: u64,
     receiver: Receiver<DropEvent>,
     sender: Sender<DropEvent>,
     assets: HashMap<HandleId, Asset>,
+    loaders: HashMap<String, Arc<dyn ErasedAssetLoader>>,
+    /// Assets produced by loaders
+    typed_assets: HashMap<HandleId, Box<dyn Any + Send + Sync>>,
+    states: HashMap<HandleId, LoadState>,
+    /// Loaded handles by path, loading a path again returns the same handle
+    paths: HashMap<(PathBuf, AssetType), Weak<StrongHandle>>,
+    /// Dependencies hold by assets
+    dependencies: HashMap<HandleId, Vec<Handle>>,
+    /// Texture data used when a texture failed to load
+    missing_texture: Option<(Vec<u8>, UVec2)>,
+    hot_reload: bool,
+    watched: HashMap<HandleId, WatchedAsset>,
+    last_poll: f32,
+    /// Memory held by each asset
+    memory: HashMap<HandleId, (AssetType, usize)>,
+    memory_budget: Option<usize>,
+    over_budget: bool,
+    pinned: HashSet<HandleId>,
+    /// Source path of loaded assets, used to reload evicted assets
+    sources: HashMap<HandleId, (PathBuf, Weak<StrongHandle>)>,
+    /// Frame of last draw
+    last_used: HashMap<HandleId, u64>,
+    evicted: HashSet<HandleId>,
+    frame: u64,
 }
 
 impl AssetManager {
     pub fn new<P: AsRef<Path>>(path: P) -> Self {
         let (sender, receiver) = channel();
         Self {
-            reader: get_default_reader(path),
+            reader: get_default_reader(path).into(),
             pending: Vec::default(),
+            job_id: 0,
+            in_flight: Default::default(),
+            ready: Default::default(),
+            upload_budget: DEFAULT_UPLOAD_BUDGET,
+            #[cfg(not(target_arch = "wasm32"))]
+            workers: None,
             asset_id: 0,
             sender,
             receiver,
             assets: Default::default(),
+            loaders: Default::default(),
+            typed_assets: Default::default(),
+            states: Default::default(),
+            paths: Default::default(),
+            dependencies: Default::default(),
+            missing_texture: None,
+            hot_reload: cfg!(debug_assertions),
+            watched: Default::default(),
+            last_poll: 0.0,
+            memory: Default::default(),
+            memory_budget: None,
+            over_budget: false,
+            pinned: Default::default(),
+            sources: Default::default(),
+            last_used: Default::default(),
+            evicted: Default::default(),
+            frame: 0,
         }
     }
 
@@ -81,14 +212,33 @@ impl AssetManager {
         Handle::new(id, drop_sender)
     }
 
+    /// Load an asset, return the alive handle if the path is already loaded,
+    /// a failed asset is loaded again with a new handle
     pub fn load<P: AsRef<Path>>(&mut self, path: P, asset_type: AssetType) -> Handle {
+        let key = (path.as_ref().to_owned(), asset_type);
+        if let Some(handle) = self.paths.get(&key).and_then(Weak::upgrade).map(Handle) {
+            let failed = self
+                .states
+                .get(&handle.id())
+                .is_some_and(LoadState::is_failed);
+            if !failed {
+                return handle;
+            }
+        }
         let handle = self.alloc_handle();
+        self.paths.insert(key, Arc::downgrade(&handle.0));
+        self.sources.insert(
+            handle.id(),
+            (path.as_ref().to_owned(), Arc::downgrade(&handle.0)),
+        );
         let task = PendingTask {
             handle: handle.clone(),
             path: path.as_ref().to_owned(),
             asset_type,
+            reload: false,
         };
         self.pending.push(task);
+        self.states.insert(handle.id(), LoadState::Pending);
         if self.pending.len() > 4096 {
             log::warn!("Too many pending tasks");
         }
@@ -98,6 +248,7 @@ impl AssetManager {
     pub fn insert(&mut self, asset: Asset) -> Handle {
         let handle = self.alloc_handle();
         self.assets.insert(handle.id(), asset);
+        self.states.insert(handle.id(), LoadState::Loaded);
         if self.assets.len() > 4096 {
             log::warn!("Too many assets");
         }
@@ -116,6 +267,275 @@ impl AssetManager {
         self.load(path, AssetType::Raw)
     }
 
+    /// Load all frames of GIF or APNG into one texture,
+    /// get frames by `get::<SpriteFrames>`
+    pub fn load_frames<P: AsRef<Path>>(&mut self, path: P) -> Handle {
+        self.load(path, AssetType::Frames)
+    }
+
+    /// Replace the reader, assets are read from it since next fetch,
+    /// loading a path again reads it from the new reader
+    pub fn set_reader<R: ErasedAssetReader>(&mut self, reader: R) {
+        self.reader = Arc::new(reader);
+        self.paths.clear();
+    }
+
+    /// Set bytes of textures uploaded per frame, a texture larger than the budget
+    /// is uploaded alone in a frame
+    pub fn set_upload_budget(&mut self, bytes: usize) {
+        self.upload_budget = bytes;
+    }
+
+    /// Load an asset with the loader registered for the file extension
+    pub fn load_asset<P: AsRef<Path>>(&mut self, path: P) -> Handle {
+        self.load(path, AssetType::Custom)
+    }
+
+    /// Register a loader, replace the previous loaders of the same extensions
+    pub fn register_loader<L: AssetLoader>(&mut self, loader: L) {
+        let loader = Arc::new(loader);
+        for ext in loader.extensions() {
+            self.loaders.insert(ext.to_lowercase(), loader.clone());
+        }
+    }
+
+    /// Get an asset produced by a loader,
+    /// return None if the asset is not loaded or has a different type
+    pub fn get<T: Any>(&self, handle: &Handle) -> Option<&T> {
+        self.typed_assets.get(&handle.id())?.downcast_ref()
+    }
+
+    pub fn get_mut<T: Any>(&mut self, handle: &Handle) -> Option<&mut T> {
+        self.typed_assets.get_mut(&handle.id())?.downcast_mut()
+    }
+
+    /// Get load state of the asset
+    /// the asset is loaded once all its dependencies are loaded
+    pub fn load_state(&self, handle: &Handle) -> LoadState {
+        let mut visited = Vec::new();
+        self.load_state_recursive(handle.id(), &mut visited)
+    }
+
+    fn load_state_recursive(&self, id: HandleId, visited: &mut Vec<HandleId>) -> LoadState {
+        if visited.contains(&id) {
+            return LoadState::Loaded;
+        }
+        visited.push(id);
+        let state = self.states.get(&id).cloned().unwrap_or(LoadState::Pending);
+        if !state.is_loaded() {
+            return state;
+        }
+        let mut pending = false;
+        for dep in self.dependencies.get(&id).into_iter().flatten() {
+            match self.load_state_recursive(dep.id(), visited) {
+                LoadState::Loaded => {}
+                LoadState::Pending => pending = true,
+                LoadState::Failed(err) => return LoadState::Failed(err),
+            }
+        }
+        if pending {
+            LoadState::Pending
+        } else {
+            LoadState::Loaded
+        }
+    }
+
+    /// Dependencies of the asset
+    pub fn dependencies(&self, handle: &Handle) -> &[Handle] {
+        self.dependencies
+            .get(&handle.id())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Set texture data which is used when a texture failed to load
+    pub fn set_missing_texture(&mut self, data: Vec<u8>, size: UVec2) {
+        self.missing_texture.replace((data, size));
+    }
+
+    /// Set image which is used when a texture failed to load
+    pub fn set_missing_texture_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        let im = image::load_from_memory(bytes)?.into_rgba8();
+        let size = UVec2::new(im.width(), im.height());
+        self.set_missing_texture(im.into_raw(), size);
+        Ok(())
+    }
+
+    /// Enable or disable hot reloading of textures and fonts,
+    /// it is enabled by default in debug builds
+    pub fn set_hot_reload(&mut self, enabled: bool) {
+        self.hot_reload = enabled;
+        if !enabled {
+            self.watched.clear();
+        }
+    }
+
+    pub fn is_hot_reload(&self) -> bool {
+        self.hot_reload
+    }
+
+    /// Check watched files, re-queue the changed ones
+    pub(crate) fn poll_changes(&mut self, now: f32) {
+        if !self.hot_reload || now - self.last_poll < HOT_RELOAD_INTERVAL {
+            return;
+        }
+        self.last_poll = now;
+        self.watched
+            .retain(|_id, watched| watched.handle.strong_count() > 0);
+        for watched in self.watched.values_mut() {
+            let Some(modified) = self.reader.modified(&watched.path.to_string_lossy()) else {
+                continue;
+            };
+            if modified == watched.modified {
+                continue;
+            }
+            let Some(handle) = watched.handle.upgrade() else {
+                continue;
+            };
+            watched.modified = modified;
+            log::info!("Reload asset {}", watched.path.display());
+            self.pending.push(PendingTask {
+                handle: Handle(handle),
+                asset_type: watched.asset_type,
+                path: watched.path.clone(),
+                reload: true,
+            });
+        }
+    }
+
+    /// Memory held by assets, evicted assets are not counted
+    pub fn memory_stats(&self) -> MemoryStats {
+        let mut stats = MemoryStats {
+            evicted: self.evicted.len(),
+            ..Default::default()
+        };
+        for (id, (asset_type, bytes)) in &self.memory {
+            if self.evicted.contains(id) {
+                continue;
+            }
+            match asset_type {
+                AssetType::Texture | AssetType::Frames => stats.textures += bytes,
+                AssetType::Font => stats.fonts += bytes,
+                AssetType::Raw => stats.raw += bytes,
+                AssetType::Custom => stats.custom += bytes,
+            }
+        }
+        stats
+    }
+
+    /// Memory held by the asset in bytes
+    pub fn memory_usage(&self, handle: &Handle) -> usize {
+        if self.evicted.contains(&handle.id()) {
+            return 0;
+        }
+        self.memory.get(&handle.id()).map_or(0, |(_, bytes)| *bytes)
+    }
+
+    /// Set memory budget in bytes, over budget the least recently drawn textures
+    /// are evicted and reloaded on next draw. None means no budget
+    pub fn set_memory_budget(&mut self, budget: Option<usize>) {
+        self.memory_budget = budget;
+        self.over_budget = false;
+    }
+
+    pub fn memory_budget(&self) -> Option<usize> {
+        self.memory_budget
+    }
+
+    /// Pinned assets are never evicted
+    pub fn pin(&mut self, handle: &Handle) {
+        self.pinned.insert(handle.id());
+    }
+
+    pub fn unpin(&mut self, handle: &Handle) {
+        self.pinned.remove(&handle.id());
+    }
+
+    pub fn is_pinned(&self, handle: &Handle) -> bool {
+        self.pinned.contains(&handle.id())
+    }
+
+    /// Whether the asset is evicted by the memory budget
+    pub fn is_evicted(&self, handle: &Handle) -> bool {
+        self.evicted.contains(&handle.id())
+    }
+
+    /// Record memory held by an asset
+    pub(crate) fn track_memory(&mut self, id: HandleId, asset_type: AssetType, bytes: usize) {
+        self.memory.insert(id, (asset_type, bytes));
+    }
+
+    /// Record drawn textures, evicted textures are reloaded
+    pub(crate) fn mark_used(&mut self, ids: impl IntoIterator<Item = HandleId>) {
+        for id in ids {
+            self.last_used.insert(id, self.frame);
+            if !self.evicted.remove(&id) {
+                continue;
+            }
+            let (Some((path, handle)), Some((asset_type, _))) =
+                (self.sources.get(&id), self.memory.get(&id))
+            else {
+                continue;
+            };
+            let Some(handle) = handle.upgrade() else {
+                continue;
+            };
+            log::debug!("Reload evicted asset {}", path.display());
+            self.pending.push(PendingTask {
+                handle: Handle(handle),
+                asset_type: *asset_type,
+                path: path.clone(),
+                reload: true,
+            });
+        }
+    }
+
+    /// Evict least recently drawn textures until the memory is under budget
+    fn evict(&mut self, tasks: &mut Vec<FetchedTask>) {
+        let Some(budget) = self.memory_budget else {
+            return;
+        };
+        let mut total = self.memory_stats().total();
+        if total <= budget {
+            self.over_budget = false;
+            return;
+        }
+        let mut candidates: Vec<_> = self
+            .memory
+            .iter()
+            .filter(|(id, (asset_type, _))| {
+                matches!(asset_type, AssetType::Texture | AssetType::Frames)
+                    && !self.pinned.contains(*id)
+                    && !self.evicted.contains(*id)
+                    && self.sources.contains_key(*id)
+                    && self.states.get(*id).is_some_and(LoadState::is_loaded)
+                    && self
+                        .last_used
+                        .get(*id)
+                        .is_none_or(|frame| *frame < self.frame)
+            })
+            .map(|(id, (_, bytes))| (*id, *bytes, self.last_used.get(id).copied()))
+            .collect();
+        candidates.sort_by_key(|(_, _, last_used)| *last_used);
+        for (id, bytes, _) in candidates {
+            if total <= budget {
+                break;
+            }
+            self.evicted.insert(id);
+            tasks.push(FetchedTask::RemoveTexture { handle: id });
+            total -= bytes;
+        }
+        if total > budget && !self.over_budget {
+            log::warn!("Asset memory {total} bytes exceeds budget {budget} bytes");
+        }
+        self.over_budget = total > budget;
+    }
+
+    /// Tasks waiting to be fetched, read, decoded or uploaded
+    pub fn pending_tasks(&self) -> usize {
+        self.pending.len() + self.in_flight.len()
+    }
+
     pub fn get_asset(&self, handle: &Handle) -> Option<&Asset> {
         self.assets.get(&handle.id())
     }
@@ -126,18 +546,30 @@ impl AssetManager {
 
     /// Fetch pending assets
     /// return completed tasks
-    pub(crate) async fn fetch(&mut self) -> Result<Vec<FetchedTask>> {
+    pub(crate) async fn fetch(&mut self) -> Vec<FetchedTask> {
         let mut tasks = Vec::default();
         // remove dropped assets
+        let mut dropped = false;
         while let Ok(event) = self.receiver.try_recv() {
+            dropped = true;
+            self.states.remove(&event.0);
+            // release dependencies, they are removed once dropped
+            self.dependencies.remove(&event.0);
+            self.memory.remove(&event.0);
+            self.pinned.remove(&event.0);
+            self.sources.remove(&event.0);
+            self.last_used.remove(&event.0);
+            self.evicted.remove(&event.0);
+            self.watched.remove(&event.0);
+            self.typed_assets.remove(&event.0);
             let Some(asset) = self.assets.remove(&event.0) else {
                 continue;
             };
             match asset.asset_type {
-                AssetType::Raw => {
+                AssetType::Raw | AssetType::Custom => {
                     // nothing todo
                 }
-                AssetType::Texture => {
+                AssetType::Texture | AssetType::Frames => {
                     let fetched_task = FetchedTask::RemoveTexture { handle: event.0 };
                     tasks.push(fetched_task);
                 }
@@ -147,58 +579,570 @@ impl AssetManager {
                 }
             }
         }
-        let pending = mem::take(&mut self.pending);
-        // TODO Use task pool to poll assets
-        for task in pending {
-            let bytes = self.reader.read(task.path.to_str().unwrap()).await?;
-            match task.asset_type {
-                AssetType::Raw => {
-                    self.assets.insert(
-                        task.handle.id(),
-                        Asset {
-                            bytes: Some(bytes),
-                            asset_type: AssetType::Raw,
-                        },
-                    );
-                }
-                AssetType::Texture => {
-                    let im = match image::ImageFormat::from_path(&task.path) {
-                        Ok(f) => image::load_from_memory_with_format(&bytes, f),
-                        _ => image::load_from_memory(&bytes),
-                    }?;
-                    let size = UVec2::new(im.width(), im.height());
-                    let data = im.into_bytes();
-                    let fetched_task = FetchedTask::CreateTexture {
-                        handle: task.handle.clone(),
-                        data,
-                        size,
-                    };
-                    tasks.push(fetched_task);
-                    self.assets.insert(
-                        task.handle.id(),
-                        Asset {
-                            bytes: None,
-                            asset_type: AssetType::Texture,
-                        },
-                    );
-                }
-                AssetType::Font => {
-                    let font = Font::from_bytes(bytes).ok_or(anyhow!("Failed to load font"))?;
-                    let fetched_task = FetchedTask::CreateFont {
-                        handle: task.handle.clone(),
-                        font,
-                    };
-                    tasks.push(fetched_task);
-                    self.assets.insert(
-                        task.handle.id(),
-                        Asset {
-                            bytes: None,
-                            asset_type: AssetType::Font,
-                        },
-                    );
-                }
+        if dropped {
+            self.paths.retain(|_key, handle| handle.strong_count() > 0);
+        }
+        self.evict(&mut tasks);
+        self.frame += 1;
+        self.start_jobs().await;
+
+        // upload decoded assets within the budget,
+        // at least one asset is uploaded per frame
+        let mut uploaded = 0;
+        while let Some((_, result)) = self.ready.front() {
+            let bytes = result.as_ref().map_or(0, Decoded::upload_bytes);
+            if uploaded > 0 && uploaded + bytes > self.upload_budget {
+                break;
+            }
+            uploaded += bytes;
+            let (job_id, result) = self.ready.pop_front().expect("ready");
+            let Some(task) = self.in_flight.remove(&job_id) else {
+                log::debug!("Ignore result of unknown job {job_id}");
+                continue;
+            };
+            self.finish(task, result, &mut tasks);
+        }
+        tasks
+    }
+
+    fn new_job(&mut self, task: PendingTask) -> Job {
+        let id = self.job_id;
+        self.job_id += 1;
+        let loader = match task.asset_type {
+            AssetType::Custom => task
+                .path
+                .extension()
+                .and_then(|ext| self.loaders.get(&ext.to_string_lossy().to_lowercase()))
+                .cloned(),
+            _ => None,
+        };
+        let job = Job {
+            id,
+            path: task.path.clone(),
+            asset_type: task.asset_type,
+            reader: self.reader.clone(),
+            loader,
+        };
+        self.in_flight.insert(id, task);
+        job
+    }
+
+    /// Read and decode pending assets on worker threads
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn start_jobs(&mut self) {
+        for task in mem::take(&mut self.pending) {
+            let job = self.new_job(task);
+            self.workers.get_or_insert_with(WorkerPool::new).submit(job);
+        }
+        if let Some(workers) = self.workers.as_ref() {
+            while let Some(result) = workers.try_recv() {
+                self.ready.push_back(result);
+            }
+        }
+    }
+
+    /// Read and decode pending assets concurrently
+    #[cfg(target_arch = "wasm32")]
+    async fn start_jobs(&mut self) {
+        let jobs: Vec<_> = mem::take(&mut self.pending)
+            .into_iter()
+            .map(|task| run_job(self.new_job(task)))
+            .collect();
+        self.ready.extend(join_all(jobs).await);
+    }
+
+    /// Finish a job, update the load state
+    fn finish(&mut self, task: PendingTask, result: Result<Decoded>, tasks: &mut Vec<FetchedTask>) {
+        let path = task.path.to_string_lossy();
+        let state = match result {
+            Ok(decoded) => {
+                self.process(&task, decoded, tasks);
+                self.watch(&task);
+                LoadState::Loaded
+            }
+            Err(err) if task.reload => {
+                // keep the previous asset, retry on next change
+                log::warn!("Failed to reload asset {path}: {err:?}");
+                return;
+            }
+            Err(err) => {
+                log::error!("Failed to load asset {path}: {err:?}");
+                self.process_failed(&task, tasks);
+                LoadState::Failed(format!("{err:#}"))
+            }
+        };
+        self.states.insert(task.handle.id(), state);
+    }
+
+    /// Watch file of the loaded asset
+    fn watch(&mut self, task: &PendingTask) {
+        if !self.hot_reload || matches!(task.asset_type, AssetType::Raw) {
+            return;
+        }
+        let Some(modified) = self.reader.modified(&task.path.to_string_lossy()) else {
+            return;
+        };
+        self.watched.insert(
+            task.handle.id(),
+            WatchedAsset {
+                handle: Arc::downgrade(&task.handle.0),
+                asset_type: task.asset_type,
+                path: task.path.clone(),
+                modified,
+            },
+        );
+    }
+
+    /// Process decoded asset
+    fn process(&mut self, task: &PendingTask, decoded: Decoded, tasks: &mut Vec<FetchedTask>) {
+        let handle = task.handle.clone();
+        self.track_memory(task.handle.id(), task.asset_type, decoded.memory());
+        let (asset_type, bytes) = match decoded {
+            Decoded::Raw(bytes) => (AssetType::Raw, Some(bytes)),
+            Decoded::Texture { data, size } => {
+                tasks.push(FetchedTask::CreateTexture { handle, data, size });
+                (AssetType::Texture, None)
+            }
+            Decoded::Frames(sheet) => {
+                let FrameSheet { data, size, frames } = sheet;
+                tasks.push(FetchedTask::CreateTexture { handle, data, size });
+                self.typed_assets.insert(task.handle.id(), Box::new(frames));
+                (AssetType::Frames, None)
+            }
+            Decoded::Font { font, .. } => {
+                tasks.push(FetchedTask::CreateFont { handle, font });
+                (AssetType::Font, None)
+            }
+            Decoded::Custom {
+                loaded: (asset, dependencies),
+                ..
+            } => {
+                self.typed_assets.insert(task.handle.id(), asset);
+                let dependencies = dependencies
+                    .into_iter()
+                    .map(|(path, asset_type)| self.load(path, asset_type))
+                    .collect();
+                self.dependencies.insert(task.handle.id(), dependencies);
+                (AssetType::Custom, None)
             }
+        };
+        self.assets
+            .insert(task.handle.id(), Asset { asset_type, bytes });
+    }
+
+    /// Use the missing texture as fallback of failed textures
+    fn process_failed(&mut self, task: &PendingTask, tasks: &mut Vec<FetchedTask>) {
+        if !matches!(task.asset_type, AssetType::Texture) {
+            return;
         }
-        Ok(tasks)
+        let Some((data, size)) = self.missing_texture.as_ref() else {
+            return;
+        };
+        let (data, size) = (data.clone(), *size);
+        self.track_memory(task.handle.id(), AssetType::Texture, data.len());
+        tasks.push(FetchedTask::CreateTexture {
+            handle: task.handle.clone(),
+            data,
+            size,
+        });
+        self.assets.insert(
+            task.handle.id(),
+            Asset {
+                bytes: None,
+                asset_type: AssetType::Texture,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use glam::UVec2;
+
+    use super::{
+        pack::{Pack, PackBuilder},
+        AssetLoader, AssetManager, AssetReader, AssetType, Dependency, EmbeddedAssetReader,
+        FetchedTask, JsonLoader, LoadState, PackAssetReader,
+    };
+
+    /// Fetch until all tasks are finished, panic if they are not finished in time
+    fn fetch_all(assets: &mut AssetManager) -> Vec<FetchedTask> {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut tasks = Vec::new();
+        loop {
+            tasks.extend(pollster::block_on(assets.fetch()));
+            let pending = assets.pending_tasks();
+            if pending == 0 {
+                return tasks;
+            }
+            if Instant::now() > deadline {
+                panic!("Timeout fetching assets, {pending} tasks are pending");
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Encode a png with the size
+    fn png(size: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image::RgbaImage::new(size, size)
+            .write_to(
+                &mut std::io::Cursor::new(&mut bytes),
+                image::ImageFormat::Png,
+            )
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_upload_budget() {
+        let mut builder = PackBuilder::new();
+        builder.add("a.png", png(4)).add("b.png", png(4));
+        let pack = Pack::from_bytes(builder.to_bytes()).unwrap();
+
+        let mut assets = AssetManager::new("assets");
+        assets.set_reader(PackAssetReader::from_pack(pack));
+        // one 4x4 texture per frame
+        assets.set_upload_budget(4 * 4 * 4);
+        let a = assets.load_texture("a.png");
+        let b = assets.load_texture("b.png");
+
+        let mut uploads = Vec::new();
+        while assets.pending_tasks() > 0 {
+            let tasks = pollster::block_on(assets.fetch());
+            assert!(tasks.len() <= 1);
+            uploads.extend(tasks.into_iter().map(|task| match task {
+                FetchedTask::CreateTexture { handle, .. } => handle,
+                _ => panic!("unexpected task"),
+            }));
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(uploads.len(), 2);
+        assert!(uploads.contains(&a) && uploads.contains(&b));
+    }
+
+    #[test]
+    fn test_failed_asset_not_abort_others() {
+        let mut assets = AssetManager::new("assets");
+        assets.set_missing_texture(vec![255, 0, 255, 255], UVec2::splat(1));
+        let missing = assets.load_texture("missing.png");
+        let font = assets.load_font("Pixel Square 10.ttf");
+        assert_eq!(assets.load_state(&missing), LoadState::Pending);
+
+        let tasks = fetch_all(&mut assets);
+        assert!(assets.load_state(&missing).is_failed());
+        assert!(assets.load_state(&font).is_loaded());
+        // the missing texture is created for the failed handle
+        assert!(tasks.iter().any(|task| matches!(
+            task,
+            FetchedTask::CreateTexture { handle, size, .. } if *handle == missing && *size == UVec2::splat(1)
+        )));
+        assert!(tasks
+            .iter()
+            .any(|task| matches!(task, FetchedTask::CreateFont { handle, .. } if *handle == font)));
+    }
+
+    #[test]
+    fn test_hot_reload() {
+        let dir = std::env::temp_dir().join(format!("roast2d-hot-reload-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("font.ttf");
+        fs::copy(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/assets/Pixel Square 10.ttf"),
+            &path,
+        )
+        .unwrap();
+
+        let mut assets = AssetManager::new(&dir);
+        assets.set_hot_reload(true);
+        let font = assets.load_font("font.ttf");
+        fetch_all(&mut assets);
+        assert!(assets.load_state(&font).is_loaded());
+
+        // unchanged file is not reloaded
+        assets.poll_changes(1.0);
+        assert_eq!(assets.pending_tasks(), 0);
+
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        let modified = file.metadata().unwrap().modified().unwrap();
+        file.set_modified(modified + Duration::from_secs(10))
+            .unwrap();
+        assets.poll_changes(2.0);
+        assert_eq!(assets.pending_tasks(), 1);
+
+        let tasks = fetch_all(&mut assets);
+        assert!(tasks
+            .iter()
+            .any(|task| matches!(task, FetchedTask::CreateFont { handle, .. } if *handle == font)));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_asset_loader() {
+        let dir = std::env::temp_dir().join(format!("roast2d-loader-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("config.json"), "[1, 2, 3]").unwrap();
+
+        let mut assets = AssetManager::new(&dir);
+        assets.register_loader(JsonLoader::<Vec<u32>>::new(&["json"]));
+        let config = assets.load_asset("config.json");
+        let unknown = assets.load_asset("config.yaml");
+        fetch_all(&mut assets);
+
+        assert_eq!(assets.get::<Vec<u32>>(&config), Some(&vec![1, 2, 3]));
+        assert!(assets.get::<String>(&config).is_none());
+        assert!(assets.load_state(&unknown).is_failed());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_pack_reader() {
+        let mut builder = PackBuilder::new();
+        builder.add("config.json", b"[4, 5]".to_vec());
+        let pack = Pack::from_bytes(builder.to_bytes()).unwrap();
+
+        let mut assets = AssetManager::new("assets");
+        assets.set_reader(PackAssetReader::from_pack(pack));
+        assets.register_loader(JsonLoader::<Vec<u32>>::new(&["json"]));
+        let config = assets.load_asset("config.json");
+        let missing = assets.load_bytes("missing.txt");
+        fetch_all(&mut assets);
+
+        assert_eq!(assets.get::<Vec<u32>>(&config), Some(&vec![4, 5]));
+        assert!(assets.load_state(&missing).is_failed());
+    }
+
+    /// Count reads of the pack file, the pack is `None` to fail reads
+    struct CountingReader {
+        pack: Option<Vec<u8>>,
+        reads: Arc<AtomicUsize>,
+    }
+
+    impl AssetReader for CountingReader {
+        fn new<P: AsRef<Path>>(_path: P) -> Self {
+            Self {
+                pack: None,
+                reads: Arc::default(),
+            }
+        }
+
+        fn get_full_path(&self, path: &str) -> PathBuf {
+            PathBuf::from(path)
+        }
+
+        async fn read<'a>(&'a self, _path: &'a str) -> anyhow::Result<Vec<u8>> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            // keep the fetch in flight while other reads start
+            thread::sleep(Duration::from_millis(10));
+            self.pack
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("Not found"))
+        }
+    }
+
+    #[test]
+    fn test_pack_reader_fetch_once() {
+        let mut builder = PackBuilder::new();
+        builder
+            .add("a.txt", b"a".to_vec())
+            .add("b.txt", b"b".to_vec());
+        for pack in [Some(builder.to_bytes()), None] {
+            let reads = Arc::new(AtomicUsize::new(0));
+            let reader = CountingReader {
+                pack: pack.clone(),
+                reads: reads.clone(),
+            };
+            let mut assets = AssetManager::new("assets");
+            assets.set_reader(PackAssetReader::with_reader(reader, "assets.pack"));
+            let handles: Vec<_> = ["a.txt", "b.txt", "c.txt", "d.txt"]
+                .into_iter()
+                .map(|path| assets.load_bytes(path))
+                .collect();
+            fetch_all(&mut assets);
+
+            assert_eq!(reads.load(Ordering::SeqCst), 1);
+            let loaded = handles
+                .iter()
+                .filter(|h| assets.load_state(h).is_loaded())
+                .count();
+            assert_eq!(loaded, if pack.is_some() { 2 } else { 0 });
+        }
+    }
+
+    #[test]
+    fn test_embedded_reader() {
+        let reader =
+            crate::embedded_assets!("assets", ["Pixel Square 10.ttf"]).embed("config.json", b"[6]");
+        let mut assets = AssetManager::new("assets");
+        assets.set_reader(reader);
+        assets.register_loader(JsonLoader::<Vec<u32>>::new(&["json"]));
+        let font = assets.load_font("Pixel Square 10.ttf");
+        let config = assets.load_asset("config.json");
+        fetch_all(&mut assets);
+        assert!(assets.load_state(&font).is_loaded());
+        assert_eq!(assets.get::<Vec<u32>>(&config), Some(&vec![6]));
+
+        // without fallback
+        assets.set_reader(EmbeddedAssetReader::empty().embed("a.json", b"[]"));
+        let missing = assets.load_asset("config.json");
+        fetch_all(&mut assets);
+        assert!(assets.load_state(&missing).is_failed());
+    }
+
+    /// Load a list of paths, each path is a raw dependency
+    struct ListLoader;
+
+    impl AssetLoader for ListLoader {
+        type Asset = Vec<String>;
+
+        fn extensions(&self) -> &[&str] {
+            &["list"]
+        }
+
+        fn load(&self, bytes: Vec<u8>, _path: &Path) -> anyhow::Result<Vec<String>> {
+            Ok(serde_json::from_slice(&bytes)?)
+        }
+
+        fn dependencies(&self, asset: &Vec<String>, _path: &Path) -> Vec<Dependency> {
+            asset
+                .iter()
+                .map(|path| (PathBuf::from(path), AssetType::Raw))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_dependencies() {
+        let reader = EmbeddedAssetReader::empty()
+            .embed("a.list", br#"["a.txt", "b.txt"]"#)
+            .embed("bad.list", br#"["a.txt", "missing.txt"]"#)
+            .embed("a.txt", b"a")
+            .embed("b.txt", b"b");
+        let mut assets = AssetManager::new("assets");
+        assets.set_reader(reader);
+        assets.register_loader(ListLoader);
+
+        let list = assets.load_asset("a.list");
+        let bad = assets.load_asset("bad.list");
+        // loading a path again returns the same handle
+        assert_eq!(assets.load_asset("a.list"), list);
+        fetch_all(&mut assets);
+
+        assert!(assets.load_state(&list).is_loaded());
+        assert!(assets.load_state(&bad).is_failed());
+        let deps = assets.dependencies(&list).to_vec();
+        assert_eq!(deps.len(), 2);
+        // shared dependency
+        assert_eq!(assets.load_bytes("a.txt"), deps[0]);
+        assert_eq!(assets.dependencies(&bad)[0], deps[0]);
+        // a failed path is loaded again
+        let missing = assets.load_bytes("missing.txt");
+        assert_ne!(missing, assets.dependencies(&bad)[1]);
+        fetch_all(&mut assets);
+        drop(deps);
+        drop(missing);
+
+        // dependencies are released with the parent
+        drop(list);
+        drop(bad);
+        fetch_all(&mut assets);
+        fetch_all(&mut assets);
+        assert!(assets.assets.is_empty());
+        assert!(assets.paths.is_empty());
+    }
+
+    /// Panic on load
+    struct PanicLoader;
+
+    impl AssetLoader for PanicLoader {
+        type Asset = ();
+
+        fn extensions(&self) -> &[&str] {
+            &["panic"]
+        }
+
+        fn load(&self, _bytes: Vec<u8>, _path: &Path) -> anyhow::Result<()> {
+            panic!("bad asset");
+        }
+    }
+
+    #[test]
+    fn test_panicking_loader() {
+        // more panics than workers
+        let paths: Vec<_> = (0..8).map(|i| format!("{i}.panic")).collect();
+        let reader = paths
+            .iter()
+            .fold(EmbeddedAssetReader::empty(), |reader, path| {
+                reader.embed(path, b"")
+            })
+            .embed("a.json", b"[1]");
+        let mut assets = AssetManager::new("assets");
+        assets.set_reader(reader);
+        assets.register_loader(PanicLoader);
+        assets.register_loader(JsonLoader::<Vec<u32>>::new(&["json"]));
+
+        let panics: Vec<_> = paths.iter().map(|path| assets.load_asset(path)).collect();
+        fetch_all(&mut assets);
+        for handle in &panics {
+            let state = assets.load_state(handle);
+            assert!(matches!(state, LoadState::Failed(err) if err.contains("bad asset")));
+        }
+
+        // workers are still alive
+        let config = assets.load_asset("a.json");
+        fetch_all(&mut assets);
+        assert_eq!(assets.get::<Vec<u32>>(&config), Some(&vec![1]));
+    }
+
+    #[test]
+    fn test_memory_budget() {
+        let mut builder = PackBuilder::new();
+        builder
+            .add("a.png", png(4))
+            .add("b.png", png(4))
+            .add("c.png", png(4));
+        let pack = Pack::from_bytes(builder.to_bytes()).unwrap();
+
+        let mut assets = AssetManager::new("assets");
+        assets.set_reader(PackAssetReader::from_pack(pack));
+        let a = assets.load_texture("a.png");
+        let b = assets.load_texture("b.png");
+        let c = assets.load_texture("c.png");
+        fetch_all(&mut assets);
+        assert_eq!(assets.memory_stats().textures, 3 * 64);
+        assert_eq!(assets.memory_usage(&a), 64);
+
+        // a is drawn, c is pinned, b is evicted
+        assets.pin(&c);
+        assets.set_memory_budget(Some(2 * 64));
+        assets.mark_used([a.id()]);
+        let tasks = pollster::block_on(assets.fetch());
+        assert!(matches!(
+            tasks.as_slice(),
+            [FetchedTask::RemoveTexture { handle }] if *handle == b.id()
+        ));
+        assert!(assets.is_evicted(&b));
+        assert_eq!(assets.memory_stats().total(), 2 * 64);
+        assert_eq!(assets.memory_stats().evicted, 1);
+
+        // b is reloaded on use, a is evicted
+        assets.mark_used([b.id()]);
+        let tasks = fetch_all(&mut assets);
+        assert!(!assets.is_evicted(&b));
+        assert!(assets.is_evicted(&a));
+        assert!(tasks
+            .iter()
+            .any(|task| matches!(task, FetchedTask::CreateTexture { handle, .. } if *handle == b)));
     }
 }
@@ -0,0 +1,263 @@
+//! Pack format bundles assets into a single file
+//!
+//! Layout, all integers are little endian:
+//! - magic `R2DPACK1`
+//! - entries count: u32
+//! - entries: path length u16, utf8 path, offset u64, length u64
+//! - data blob, offsets are relative to the start of the blob
+
+use std::{
+    fs,
+    io::Write,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use hashbrown::HashMap;
+
+const MAGIC: &[u8; 8] = b"R2DPACK1";
+/// Size of an entry without the path
+const ENTRY_HEADER_SIZE: usize = 2 + 8 + 8;
+
+/// Normalize path to the form used in pack index
+fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    path.trim_start_matches("./")
+        .trim_start_matches('/')
+        .to_owned()
+}
+
+/// A loaded pack
+pub struct Pack {
+    index: HashMap<String, Range<usize>>,
+    data: Vec<u8>,
+}
+
+impl Pack {
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self> {
+        let mut cursor = Cursor {
+            bytes: &bytes,
+            pos: 0,
+        };
+        if cursor.take(MAGIC.len())? != MAGIC {
+            bail!("Invalid pack magic");
+        }
+        let count = u32::from_le_bytes(cursor.take_array()?) as usize;
+        // the count is untrusted, cap the preallocation by the entries the left bytes can hold
+        let remaining = bytes.len() - cursor.pos;
+        let mut entries = Vec::with_capacity(count.min(remaining / ENTRY_HEADER_SIZE));
+        for _ in 0..count {
+            let len = u16::from_le_bytes(cursor.take_array()?) as usize;
+            let path = std::str::from_utf8(cursor.take(len)?)
+                .context("Invalid pack path")?
+                .to_owned();
+            let offset = u64::from_le_bytes(cursor.take_array()?);
+            let len = u64::from_le_bytes(cursor.take_array()?);
+            let range = offset
+                .checked_add(len)
+                .and_then(|end| Some(usize::try_from(offset).ok()?..usize::try_from(end).ok()?))
+                .ok_or_else(|| anyhow!("Pack entry {path} out of bounds"))?;
+            entries.push((path, range));
+        }
+        let data_start = cursor.pos;
+        let data_len = bytes.len() - data_start;
+        if let Some((path, _)) = entries
+            .iter()
+            .find(|(_, range)| range.start > range.end || range.end > data_len)
+        {
+            bail!("Pack entry {path} out of bounds");
+        }
+        let mut data = bytes;
+        data.drain(..data_start);
+        Ok(Self {
+            index: entries.into_iter().collect(),
+            data,
+        })
+    }
+
+    /// Get bytes of a file
+    pub fn get(&self, path: &str) -> Option<&[u8]> {
+        let range = self.index.get(&normalize_path(path))?;
+        Some(&self.data[range.clone()])
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.index.contains_key(&normalize_path(path))
+    }
+
+    /// Iterate paths of packed files
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.index.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(self.pos..end))
+            .ok_or_else(|| anyhow!("Unexpected end of pack"))?;
+        let end = self.pos + len;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("length"))
+    }
+}
+
+/// Build a pack
+#[derive(Default)]
+pub struct PackBuilder {
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl PackBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file, replace the previous one with the same path
+    pub fn add(&mut self, path: &str, bytes: Vec<u8>) -> &mut Self {
+        let path = normalize_path(path);
+        self.files.retain(|(p, _)| p != &path);
+        self.files.push((path, bytes));
+        self
+    }
+
+    /// Add all files under the directory, paths are relative to the directory
+    pub fn add_dir<P: AsRef<Path>>(&mut self, dir: P) -> Result<&mut Self> {
+        let dir = dir.as_ref();
+        let mut stack = vec![PathBuf::new()];
+        while let Some(rel) = stack.pop() {
+            let full = dir.join(&rel);
+            let mut entries = fs::read_dir(&full)
+                .with_context(|| format!("Failed to read dir {}", full.display()))?
+                .collect::<Result<Vec<_>, _>>()?;
+            entries.sort_by_key(|e| e.file_name());
+            for entry in entries {
+                let rel = rel.join(entry.file_name());
+                if entry.file_type()?.is_dir() {
+                    stack.push(rel);
+                } else {
+                    let bytes = fs::read(entry.path())
+                        .with_context(|| format!("Failed to read {}", entry.path().display()))?;
+                    self.add(&rel.to_string_lossy(), bytes);
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn write<W: Write>(&self, mut w: W) -> Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&(self.files.len() as u32).to_le_bytes())?;
+        let mut offset = 0u64;
+        for (path, bytes) in &self.files {
+            let len = u16::try_from(path.len()).context("Pack path too long")?;
+            w.write_all(&len.to_le_bytes())?;
+            w.write_all(path.as_bytes())?;
+            w.write_all(&offset.to_le_bytes())?;
+            w.write_all(&(bytes.len() as u64).to_le_bytes())?;
+            offset += bytes.len() as u64;
+        }
+        for (_path, bytes) in &self.files {
+            w.write_all(bytes)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write(&mut buf).expect("write to vec");
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pack, PackBuilder};
+
+    #[test]
+    fn test_pack_roundtrip() {
+        let mut builder = PackBuilder::new();
+        builder
+            .add("a.txt", b"hello".to_vec())
+            .add("dir\\b.txt", b"world".to_vec())
+            .add("empty", Vec::new());
+        let pack = Pack::from_bytes(builder.to_bytes()).unwrap();
+        assert_eq!(pack.len(), 3);
+        assert_eq!(pack.get("a.txt"), Some(&b"hello"[..]));
+        assert_eq!(pack.get("./dir/b.txt"), Some(&b"world"[..]));
+        assert_eq!(pack.get("empty"), Some(&b""[..]));
+        assert!(pack.get("c.txt").is_none());
+    }
+
+    #[test]
+    fn test_invalid_pack() {
+        assert!(Pack::from_bytes(b"NOTAPACK".to_vec()).is_err());
+        let mut bytes = PackBuilder::new().add("a", vec![1, 2, 3]).to_bytes();
+        bytes.pop();
+        assert!(Pack::from_bytes(bytes).is_err());
+    }
+
+    #[test]
+    fn test_corrupt_pack() {
+        let header = |count: u32| {
+            let mut bytes = b"R2DPACK1".to_vec();
+            bytes.extend_from_slice(&count.to_le_bytes());
+            bytes
+        };
+        let entry = |offset: u64, len: u64| {
+            let mut bytes = header(1);
+            bytes.extend_from_slice(&1u16.to_le_bytes());
+            bytes.push(b'a');
+            bytes.extend_from_slice(&offset.to_le_bytes());
+            bytes.extend_from_slice(&len.to_le_bytes());
+            bytes.extend_from_slice(&[0; 8]);
+            bytes
+        };
+        // a huge count without entries
+        assert!(Pack::from_bytes(header(u32::MAX)).is_err());
+        // offset + len overflows
+        assert!(Pack::from_bytes(entry(u64::MAX, 2)).is_err());
+        assert!(Pack::from_bytes(entry(4, u64::MAX)).is_err());
+        // out of the data blob
+        assert!(Pack::from_bytes(entry(4, 8)).is_err());
+        assert!(Pack::from_bytes(entry(4, 4)).is_ok());
+    }
+
+    #[test]
+    fn test_pack_dir() {
+        let mut builder = PackBuilder::new();
+        builder
+            .add_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/assets"))
+            .unwrap();
+        let pack = Pack::from_bytes(builder.to_bytes()).unwrap();
+        assert!(pack.contains("Pixel Square 10.ttf"));
+    }
+}
@@ -0,0 +1,257 @@
+//! Read and decode assets off the main thread
+
+use std::{future::Future, path::PathBuf, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use glam::UVec2;
+
+use super::{
+    frames::{decode_frames, FrameSheet},
+    io::ErasedAssetReader,
+    loader::{ErasedAssetLoader, Loaded},
+    AssetType,
+};
+use crate::font::Font;
+
+/// A read and decode job
+pub(crate) struct Job {
+    pub id: u64,
+    pub path: PathBuf,
+    pub asset_type: AssetType,
+    pub reader: Arc<dyn ErasedAssetReader>,
+    /// Loader of custom asset
+    pub loader: Option<Arc<dyn ErasedAssetLoader>>,
+}
+
+/// Decoded asset, ready to be uploaded
+pub(crate) enum Decoded {
+    Raw(Vec<u8>),
+    Texture { data: Vec<u8>, size: UVec2 },
+    Frames(FrameSheet),
+    Font { font: Font, bytes: usize },
+    Custom { loaded: Loaded, bytes: usize },
+}
+
+impl Decoded {
+    /// Bytes uploaded to the platform
+    pub fn upload_bytes(&self) -> usize {
+        match self {
+            Self::Texture { data, .. } => data.len(),
+            Self::Frames(sheet) => sheet.data.len(),
+            _ => 0,
+        }
+    }
+
+    /// Estimated memory held by the asset, the source bytes are counted for fonts and custom assets
+    pub fn memory(&self) -> usize {
+        match self {
+            Self::Raw(bytes) => bytes.len(),
+            Self::Texture { data, .. } => data.len(),
+            Self::Frames(sheet) => sheet.data.len(),
+            Self::Font { bytes, .. } | Self::Custom { bytes, .. } => *bytes,
+        }
+    }
+}
+
+pub(crate) type JobResult = (u64, Result<Decoded>);
+
+/// Read and decode the asset
+pub(crate) async fn run_job(job: Job) -> JobResult {
+    let result = match job.reader.read(&job.path.to_string_lossy()).await {
+        Ok(bytes) => decode(&job, bytes),
+        Err(err) => Err(err),
+    };
+    (job.id, result)
+}
+
+fn decode(job: &Job, bytes: Vec<u8>) -> Result<Decoded> {
+    let decoded = match job.asset_type {
+        AssetType::Raw => Decoded::Raw(bytes),
+        AssetType::Texture => {
+            let im = match image::ImageFormat::from_path(&job.path) {
+                Ok(f) => image::load_from_memory_with_format(&bytes, f),
+                _ => image::load_from_memory(&bytes),
+            }?;
+            let size = UVec2::new(im.width(), im.height());
+            let data = im.into_bytes();
+            Decoded::Texture { data, size }
+        }
+        AssetType::Frames => Decoded::Frames(decode_frames(&job.path, &bytes)?),
+        AssetType::Font => {
+            let len = bytes.len();
+            let font = Font::from_bytes(bytes).ok_or(anyhow!("Failed to load font"))?;
+            Decoded::Font { font, bytes: len }
+        }
+        AssetType::Custom => {
+            let ext = job
+                .path
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            let loader = job
+                .loader
+                .as_ref()
+                .ok_or_else(|| anyhow!("No loader for extension {ext:?}"))?;
+            let len = bytes.len();
+            Decoded::Custom {
+                loaded: loader.load(bytes, &job.path)?,
+                bytes: len,
+            }
+        }
+    };
+    Ok(decoded)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use pool::WorkerPool;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod pool {
+    use std::{
+        any::Any,
+        future::Future,
+        panic::{self, AssertUnwindSafe},
+        sync::{
+            mpsc::{channel, Receiver, Sender},
+            Arc, Mutex,
+        },
+        task::{Context, Poll, Wake, Waker},
+        thread::{self, Thread},
+    };
+
+    use anyhow::anyhow;
+
+    use super::{run_job, Job, JobResult};
+
+    const MAX_WORKERS: usize = 4;
+
+    /// Worker threads run jobs
+    pub(crate) struct WorkerPool {
+        jobs: Sender<Job>,
+        results: Receiver<JobResult>,
+    }
+
+    impl WorkerPool {
+        pub fn new() -> Self {
+            let threads = thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+                .clamp(1, MAX_WORKERS);
+            let (jobs, jobs_receiver) = channel::<Job>();
+            let (results_sender, results) = channel();
+            let jobs_receiver = Arc::new(Mutex::new(jobs_receiver));
+            for i in 0..threads {
+                let jobs_receiver = jobs_receiver.clone();
+                let results_sender = results_sender.clone();
+                thread::Builder::new()
+                    .name(format!("roast2d-asset-{i}"))
+                    .spawn(move || loop {
+                        let job = {
+                            let Ok(receiver) = jobs_receiver.lock() else {
+                                return;
+                            };
+                            match receiver.recv() {
+                                Ok(job) => job,
+                                // the pool is dropped
+                                Err(_) => return,
+                            }
+                        };
+                        let id = job.id;
+                        // a panicking decoder or loader fails the job instead of killing the worker
+                        let result =
+                            panic::catch_unwind(AssertUnwindSafe(|| block_on(run_job(job))))
+                                .unwrap_or_else(|payload| (id, Err(panic_error(payload))));
+                        if results_sender.send(result).is_err() {
+                            return;
+                        }
+                    })
+                    .expect("spawn asset worker");
+            }
+            Self { jobs, results }
+        }
+
+        pub fn submit(&self, job: Job) {
+            self.jobs.send(job).expect("asset workers");
+        }
+
+        pub fn try_recv(&self) -> Option<JobResult> {
+            self.results.try_recv().ok()
+        }
+    }
+
+    fn panic_error(payload: Box<dyn Any + Send>) -> anyhow::Error {
+        let msg = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown panic");
+        anyhow!("Asset job panicked: {msg}")
+    }
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Run future to completion on current thread
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+}
+
+/// Poll futures concurrently, return outputs in order
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+pub(crate) async fn join_all<F: Future>(futures: Vec<F>) -> Vec<F::Output> {
+    use std::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    struct JoinAll<F: Future> {
+        futures: Vec<Pin<Box<F>>>,
+        outputs: Vec<Option<F::Output>>,
+    }
+
+    // futures are boxed, outputs are never pinned
+    impl<F: Future> Unpin for JoinAll<F> {}
+
+    impl<F: Future> Future for JoinAll<F> {
+        type Output = Vec<F::Output>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let this = self.get_mut();
+            let mut done = true;
+            for (future, output) in this.futures.iter_mut().zip(this.outputs.iter_mut()) {
+                if output.is_some() {
+                    continue;
+                }
+                match future.as_mut().poll(cx) {
+                    Poll::Ready(out) => *output = Some(out),
+                    Poll::Pending => done = false,
+                }
+            }
+            if !done {
+                return Poll::Pending;
+            }
+            Poll::Ready(this.outputs.iter_mut().map(|o| o.take().unwrap()).collect())
+        }
+    }
+
+    let outputs = futures.iter().map(|_| None).collect();
+    JoinAll {
+        futures: futures.into_iter().map(Box::pin).collect(),
+        outputs,
+    }
+    .await
+}
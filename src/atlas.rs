@@ -0,0 +1,261 @@
+//! Runtime texture atlas, pack many small images into a few large textures
+
+use anyhow::{bail, Result};
+use glam::UVec2;
+use hashbrown::HashMap;
+
+use crate::{engine::Engine, handle::Handle, sprite::Sprite, types::Rect};
+
+/// Default size of atlas pages
+const DEFAULT_PAGE_SIZE: u32 = 1024;
+
+/// Position of a packed rect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PackedRect {
+    /// Index of the page
+    pub page: usize,
+    /// Top-left position in the page
+    pub pos: UVec2,
+}
+
+/// Pack rects into pages with shelves, return positions in the order of sizes.
+///
+/// Rects are placed from the tallest to the shortest, each row of a page is a shelf,
+/// `padding` pixels are kept between rects.
+pub fn pack_rects(sizes: &[UVec2], page_size: UVec2, padding: u32) -> Result<Vec<PackedRect>> {
+    if let Some(size) = sizes
+        .iter()
+        .find(|size| size.x > page_size.x || size.y > page_size.y)
+    {
+        bail!("Image {size} is larger than the atlas page {page_size}");
+    }
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by(|a, b| {
+        sizes[*b]
+            .y
+            .cmp(&sizes[*a].y)
+            .then(sizes[*b].x.cmp(&sizes[*a].x))
+    });
+
+    let mut packed = vec![
+        PackedRect {
+            page: 0,
+            pos: UVec2::ZERO,
+        };
+        sizes.len()
+    ];
+    let mut page = 0;
+    // cursor of current shelf
+    let mut x = 0;
+    let mut y = 0;
+    let mut shelf_height = 0;
+    for index in order {
+        let size = sizes[index];
+        if x + size.x > page_size.x {
+            // next shelf
+            x = 0;
+            y += shelf_height + padding;
+            shelf_height = 0;
+        }
+        if y + size.y > page_size.y {
+            // next page
+            page += 1;
+            x = 0;
+            y = 0;
+            shelf_height = 0;
+        }
+        packed[index] = PackedRect {
+            page,
+            pos: UVec2::new(x, y),
+        };
+        x += size.x + padding;
+        shelf_height = shelf_height.max(size.y);
+    }
+    Ok(packed)
+}
+
+struct AtlasImage {
+    key: String,
+    data: Vec<u8>,
+    size: UVec2,
+}
+
+/// Build atlas from RGBA images
+pub struct AtlasBuilder {
+    page_size: UVec2,
+    padding: u32,
+    images: Vec<AtlasImage>,
+}
+
+impl Default for AtlasBuilder {
+    fn default() -> Self {
+        Self {
+            page_size: UVec2::splat(DEFAULT_PAGE_SIZE),
+            padding: 1,
+            images: Vec::new(),
+        }
+    }
+}
+
+impl AtlasBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set size of pages, default is 1024x1024
+    pub fn page_size(mut self, page_size: UVec2) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Set pixels between images, default is 1
+    pub fn padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Add RGBA pixels
+    pub fn add<K: Into<String>>(&mut self, key: K, data: Vec<u8>, size: UVec2) -> Result<()> {
+        if data.len() != (size.x * size.y * 4) as usize {
+            bail!("Image data doesn't match size {size}");
+        }
+        self.images.push(AtlasImage {
+            key: key.into(),
+            data,
+            size,
+        });
+        Ok(())
+    }
+
+    /// Add an encoded image, for example bytes loaded by `AssetManager::load_bytes`
+    pub fn add_image_bytes<K: Into<String>>(&mut self, key: K, bytes: &[u8]) -> Result<()> {
+        let im = image::load_from_memory(bytes)?.into_rgba8();
+        let size = UVec2::new(im.width(), im.height());
+        self.add(key, im.into_raw(), size)
+    }
+
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// Pack images and create textures of pages
+    pub fn build(self, g: &mut Engine) -> Result<Atlas> {
+        let sizes: Vec<_> = self.images.iter().map(|im| im.size).collect();
+        let packed = pack_rects(&sizes, self.page_size, self.padding)?;
+        let pages_count = packed.iter().map(|p| p.page + 1).max().unwrap_or(0);
+
+        let page_width = self.page_size.x as usize;
+        let mut pages = vec![vec![0u8; page_width * self.page_size.y as usize * 4]; pages_count];
+        for (im, packed) in self.images.iter().zip(&packed) {
+            let page = &mut pages[packed.page];
+            let row_bytes = im.size.x as usize * 4;
+            for row in 0..im.size.y as usize {
+                let dst = ((packed.pos.y as usize + row) * page_width + packed.pos.x as usize) * 4;
+                let src = row * row_bytes;
+                page[dst..dst + row_bytes].copy_from_slice(&im.data[src..src + row_bytes]);
+            }
+        }
+        let pages: Vec<Handle> = pages
+            .into_iter()
+            .map(|data| g.create_texture(data, self.page_size))
+            .collect();
+
+        let sprites = self
+            .images
+            .into_iter()
+            .zip(packed)
+            .map(|(im, packed)| {
+                let mut sprite = Sprite::new(pages[packed.page].clone(), im.size);
+                let min = packed.pos.as_vec2();
+                sprite.src = Some(Rect {
+                    min,
+                    max: min + im.size.as_vec2(),
+                });
+                (im.key, sprite)
+            })
+            .collect();
+        Ok(Atlas { pages, sprites })
+    }
+}
+
+/// Packed atlas
+pub struct Atlas {
+    pages: Vec<Handle>,
+    sprites: HashMap<String, Sprite>,
+}
+
+impl Atlas {
+    /// Get a sprite points into the atlas
+    pub fn get(&self, key: &str) -> Option<Sprite> {
+        self.sprites.get(key).cloned()
+    }
+
+    /// Textures of pages
+    pub fn pages(&self) -> &[Handle] {
+        &self.pages
+    }
+
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+
+    /// Iterate sprites with keys
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Sprite)> {
+        self.sprites.iter().map(|(k, v)| (k.as_str(), v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::UVec2;
+
+    use super::pack_rects;
+
+    #[test]
+    fn test_pack_no_overlap() {
+        let sizes = vec![
+            UVec2::new(10, 20),
+            UVec2::new(30, 10),
+            UVec2::new(16, 16),
+            UVec2::new(40, 5),
+            UVec2::new(8, 30),
+        ];
+        let page_size = UVec2::splat(64);
+        let packed = pack_rects(&sizes, page_size, 1).unwrap();
+        assert!(packed.iter().all(|p| p.page == 0));
+        for (i, a) in packed.iter().enumerate() {
+            let a_max = a.pos + sizes[i];
+            assert!(a_max.x <= page_size.x && a_max.y <= page_size.y);
+            for (j, b) in packed.iter().enumerate().skip(i + 1) {
+                let b_max = b.pos + sizes[j];
+                let overlap = a.pos.x < b_max.x
+                    && b.pos.x < a_max.x
+                    && a.pos.y < b_max.y
+                    && b.pos.y < a_max.y;
+                assert!(!overlap, "{i} overlaps {j}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_pack_pages() {
+        let sizes = vec![UVec2::splat(32); 5];
+        let packed = pack_rects(&sizes, UVec2::splat(64), 0).unwrap();
+        // 4 rects per page
+        assert_eq!(packed.iter().filter(|p| p.page == 0).count(), 4);
+        assert_eq!(packed.iter().filter(|p| p.page == 1).count(), 1);
+    }
+
+    #[test]
+    fn test_pack_too_large() {
+        assert!(pack_rects(&[UVec2::new(65, 1)], UVec2::splat(64), 0).is_err());
+    }
+}
@@ -6,7 +6,6 @@ use crate::{
     types::Rect,
 };
 
-#[derive(Default)]
 pub struct Camera {
     // A factor of how fast the camera is moving. Values between 0.5..10
     // are usually sensible.
@@ -38,6 +37,16 @@ pub struct Camera {
     // the deadzone that the entity touched last.
     pub look_ahead: Vec2,
 
+    // Zoom factor, values above 1 zoom in. Default is 1
+    pub zoom: f32,
+
+    // Rotation of the view in radians, around the screen center
+    pub rotation: f32,
+
+    // Rect on the screen the camera draws to, in logical pixels. The whole
+    // screen if None. Used by split-screen and picture-in-picture views.
+    pub pane: Option<Rect>,
+
     // The top left corner of the viewport. Internally just an offset when
     // drawing background_maps and entities.
     pub(crate) viewport: Vec2,
@@ -52,21 +61,85 @@ pub struct Camera {
     force: bool,
 }
 
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            speed: 0.0,
+            offset: Vec2::ZERO,
+            snap_to_platform: false,
+            is_followed_ent_on_ground: false,
+            min_vel: Vec2::ZERO,
+            deadzone: Vec2::ZERO,
+            look_ahead: Vec2::ZERO,
+            zoom: 1.0,
+            rotation: 0.0,
+            pane: None,
+            viewport: Vec2::ZERO,
+            deadzone_pos: Vec2::ZERO,
+            look_ahead_target: Vec2::ZERO,
+            follow: None,
+            pos: Vec2::ZERO,
+            vel: Vec2::ZERO,
+            snap: false,
+            force: false,
+        }
+    }
+}
+
 impl Camera {
     fn viewport_target(&self, screen_size: Vec2, bounds: Option<Vec2>) -> Vec2 {
         let screen_center = screen_size * 0.5;
         let mut viewport_target = self.pos - screen_center + self.offset;
         if let Some(bounds) = bounds {
-            viewport_target.x = viewport_target
-                .x
-                .clamp(0.0, (bounds.x - screen_size.x).max(0.0));
-            viewport_target.y = viewport_target
-                .y
-                .clamp(0.0, (bounds.y - screen_size.y).max(0.0));
+            // the visible area is scaled by zoom around the screen center
+            let margin = (self.visible_size(screen_size) - screen_size) * 0.5;
+            let max = (bounds - screen_size - margin).max(margin);
+            viewport_target = viewport_target.clamp(margin, max);
         }
         viewport_target
     }
 
+    /// Screen rect the camera draws to
+    pub fn pane_rect(&self, screen_size: Vec2) -> Rect {
+        self.pane.clone().unwrap_or(Rect {
+            min: Vec2::ZERO,
+            max: screen_size,
+        })
+    }
+
+    /// Size of the visible world area
+    pub fn visible_size(&self, screen_size: Vec2) -> Vec2 {
+        screen_size / self.zoom
+    }
+
+    /// Whether zoom or rotation is applied
+    pub fn is_transformed(&self) -> bool {
+        self.zoom != 1.0 || self.rotation != 0.0
+    }
+
+    /// Convert a world position to the screen position
+    pub fn world_to_screen(&self, pos: Vec2, screen_size: Vec2) -> Vec2 {
+        let pane = self.pane_rect(screen_size);
+        let center = (pane.max - pane.min) * 0.5;
+        let view = pos - self.viewport;
+        pane.min + center + Vec2::from_angle(-self.rotation).rotate(view - center) * self.zoom
+    }
+
+    /// Convert a screen position to the world position, for example the mouse position
+    pub fn screen_to_world(&self, pos: Vec2, screen_size: Vec2) -> Vec2 {
+        let pane = self.pane_rect(screen_size);
+        let center = (pane.max - pane.min) * 0.5;
+        let pos = pos - pane.min;
+        let view = center + Vec2::from_angle(self.rotation).rotate(pos - center) / self.zoom;
+        view + self.viewport
+    }
+
+    /// Whether the screen position is inside the camera pane
+    pub fn contains_screen_pos(&self, pos: Vec2, screen_size: Vec2) -> bool {
+        let pane = self.pane_rect(screen_size);
+        pos.cmpge(pane.min).all() && pos.cmplt(pane.max).all()
+    }
+
     pub(crate) fn update(
         &mut self,
         tick: f32,
@@ -155,3 +228,78 @@ impl Camera {
         self.follow.take();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::Camera;
+    use crate::types::Rect;
+
+    #[test]
+    fn test_world_screen_conversion() {
+        let mut camera = Camera {
+            zoom: 2.0,
+            rotation: 0.5,
+            ..Default::default()
+        };
+        camera.viewport = Vec2::new(30.0, 40.0);
+        let screen_size = Vec2::new(320.0, 240.0);
+        // the screen center is kept
+        let center = camera.world_to_screen(camera.viewport + screen_size * 0.5, screen_size);
+        assert!(center.abs_diff_eq(screen_size * 0.5, 1e-4));
+
+        let world = Vec2::new(100.0, 20.0);
+        let screen = camera.world_to_screen(world, screen_size);
+        assert!(camera
+            .screen_to_world(screen, screen_size)
+            .abs_diff_eq(world, 1e-3));
+    }
+
+    #[test]
+    fn test_zoom_aware_bounds() {
+        let screen_size = Vec2::new(100.0, 100.0);
+        let bounds = Vec2::new(400.0, 400.0);
+        let mut camera = Camera::default();
+        assert_eq!(
+            camera.viewport_target(screen_size, Some(bounds)),
+            Vec2::ZERO
+        );
+
+        // zoom out, 200x200 is visible, the view center can't be closer than 100 to the edge
+        camera.zoom = 0.5;
+        assert_eq!(
+            camera.viewport_target(screen_size, Some(bounds)),
+            Vec2::splat(50.0)
+        );
+        camera.pos = Vec2::splat(1000.0);
+        assert_eq!(
+            camera.viewport_target(screen_size, Some(bounds)),
+            Vec2::splat(250.0)
+        );
+    }
+
+    #[test]
+    fn test_pane_conversion() {
+        let screen_size = Vec2::new(320.0, 240.0);
+        let mut camera = Camera {
+            pane: Some(Rect {
+                min: Vec2::new(160.0, 0.0),
+                max: Vec2::new(320.0, 240.0),
+            }),
+            ..Default::default()
+        };
+        camera.viewport = Vec2::new(10.0, 20.0);
+        // the top left of the view is drawn at the top left of the pane
+        assert_eq!(
+            camera.world_to_screen(Vec2::new(10.0, 20.0), screen_size),
+            Vec2::new(160.0, 0.0)
+        );
+        assert_eq!(
+            camera.screen_to_world(Vec2::new(200.0, 40.0), screen_size),
+            Vec2::new(50.0, 60.0)
+        );
+        assert!(camera.contains_screen_pos(Vec2::new(200.0, 40.0), screen_size));
+        assert!(!camera.contains_screen_pos(Vec2::new(100.0, 40.0), screen_size));
+    }
+}
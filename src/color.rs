@@ -21,6 +21,21 @@ impl Color {
     }
 }
 
+/// Multiply components, used to tint colors
+impl std::ops::Mul for Color {
+    type Output = Color;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mul = |a: u8, b: u8| ((a as u32 * b as u32) / 255) as u8;
+        Color::rgba(
+            mul(self.r, rhs.r),
+            mul(self.g, rhs.g),
+            mul(self.b, rhs.b),
+            mul(self.a, rhs.a),
+        )
+    }
+}
+
 pub const WHITE: Color = Color::rgb(0xff, 0xff, 0xff);
 pub const BLACK: Color = Color::rgb(0, 0, 0);
 pub const BLUE: Color = Color::rgb(0, 0, 0xff);
@@ -0,0 +1,166 @@
+//! Deferred draw commands, sorted by layer and depth and flushed at the end of frame
+
+use std::cmp::Ordering;
+
+use hashbrown::HashMap;
+
+use crate::{
+    color::Color,
+    handle::{Handle, HandleId},
+    render::BlendMode,
+    types::Rect,
+};
+
+/// Sort key of draws, draws are sorted by layer then depth,
+/// draws with the same key keep the submission order
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct DrawKey {
+    pub layer: i32,
+    pub depth: f32,
+}
+
+impl DrawKey {
+    pub fn new(layer: i32, depth: f32) -> Self {
+        Self { layer, depth }
+    }
+
+    fn cmp_key(&self, other: &Self) -> Ordering {
+        self.layer
+            .cmp(&other.layer)
+            .then(self.depth.total_cmp(&other.depth))
+    }
+}
+
+pub(crate) struct DrawCommand {
+    pub key: DrawKey,
+    pub texture: Handle,
+    pub color: Color,
+    pub blend: BlendMode,
+    pub src: Option<Rect>,
+    pub dst: Rect,
+    pub angle: Option<f32>,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// Clip rect in screen pixels
+    pub clip: Option<Rect>,
+}
+
+/// Draw commands of a frame
+#[derive(Default)]
+pub(crate) struct DrawQueue {
+    commands: Vec<DrawCommand>,
+    /// Group draws with the same key by texture
+    pub batch_by_texture: bool,
+}
+
+impl DrawQueue {
+    pub fn push(&mut self, command: DrawCommand) {
+        self.commands.push(command);
+    }
+
+    /// Take sorted commands, return commands and the count of batches,
+    /// a batch is a run of commands with the same texture
+    pub fn take_sorted(&mut self) -> (Vec<DrawCommand>, u32) {
+        let mut commands = std::mem::take(&mut self.commands);
+        if self.batch_by_texture {
+            // order textures by the first draw, so a texture is not moved before another one
+            let mut first_draw: HashMap<HandleId, usize> = HashMap::new();
+            for (i, cmd) in commands.iter().enumerate() {
+                first_draw.entry(cmd.texture.id()).or_insert(i);
+            }
+            commands.sort_by(|a, b| {
+                a.key
+                    .cmp_key(&b.key)
+                    .then_with(|| first_draw[&a.texture.id()].cmp(&first_draw[&b.texture.id()]))
+            });
+        } else {
+            commands.sort_by(|a, b| a.key.cmp_key(&b.key));
+        }
+        let batches = commands
+            .iter()
+            .zip(commands.iter().skip(1))
+            .filter(|(a, b)| a.texture != b.texture)
+            .count()
+            + usize::from(!commands.is_empty());
+        (commands, batches as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::channel;
+
+    use glam::Vec2;
+
+    use super::{DrawCommand, DrawKey, DrawQueue};
+    use crate::{color::WHITE, handle::Handle, render::BlendMode, types::Rect};
+
+    fn command(texture: &Handle, layer: i32, depth: f32) -> DrawCommand {
+        DrawCommand {
+            key: DrawKey::new(layer, depth),
+            texture: texture.clone(),
+            color: WHITE,
+            blend: BlendMode::Alpha,
+            src: None,
+            dst: Rect {
+                min: Vec2::ZERO,
+                max: Vec2::ONE,
+            },
+            angle: None,
+            flip_x: false,
+            flip_y: false,
+            clip: None,
+        }
+    }
+
+    #[test]
+    fn test_sort_stable() {
+        let (sender, _receiver) = channel();
+        let a = Handle::new(0, sender.clone());
+        let b = Handle::new(1, sender);
+        let mut queue = DrawQueue::default();
+        queue.push(command(&a, 1, 0.0));
+        queue.push(command(&b, 0, 2.0));
+        queue.push(command(&a, 0, 1.0));
+        queue.push(command(&b, 0, 1.0));
+        queue.push(command(&a, 0, 1.0));
+
+        let (commands, batches) = queue.take_sorted();
+        let order: Vec<_> = commands
+            .iter()
+            .map(|c| (c.key.layer, c.key.depth, c.texture.id()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (0, 1.0, 0),
+                (0, 1.0, 1),
+                (0, 1.0, 0),
+                (0, 2.0, 1),
+                (1, 0.0, 0)
+            ]
+        );
+        assert_eq!(batches, 5);
+        assert!(queue.commands.is_empty());
+    }
+
+    #[test]
+    fn test_batch_by_texture() {
+        let (sender, _receiver) = channel();
+        let a = Handle::new(0, sender.clone());
+        let b = Handle::new(1, sender);
+        let mut queue = DrawQueue {
+            batch_by_texture: true,
+            ..Default::default()
+        };
+        queue.push(command(&a, 0, 0.0));
+        queue.push(command(&b, 0, 0.0));
+        queue.push(command(&a, 0, 0.0));
+        queue.push(command(&b, 1, 0.0));
+
+        let (commands, batches) = queue.take_sorted();
+        let textures: Vec<_> = commands.iter().map(|c| c.texture.id()).collect();
+        assert_eq!(textures, vec![0, 0, 1, 1]);
+        assert_eq!(batches, 2);
+    }
+}
@@ -1,24 +1,45 @@
 use std::any::{Any, TypeId};
 
-/// Entity type
-#[derive(Hash, PartialEq, Eq, Clone)]
-pub struct ComponentId(pub TypeId);
+/// Component id
+///
+/// The id was a `ComponentId(pub TypeId)` struct before runtime components,
+/// use `type_id()` in place of `.0`
+#[derive(Debug, Hash, PartialEq, Eq, Clone)]
+pub enum ComponentId {
+    /// Component defined by a Rust type
+    Static(TypeId),
+    /// Component registered at runtime, see `World::register_dynamic_component`
+    Dynamic(u32),
+}
 
 impl ComponentId {
     pub fn of<T: 'static>() -> Self {
-        Self(TypeId::of::<T>())
+        Self::Static(TypeId::of::<T>())
     }
 
     pub fn is<T: 'static>(&self) -> bool {
-        Self::of::<T>().0 == self.0
+        *self == Self::of::<T>()
+    }
+
+    pub fn is_dynamic(&self) -> bool {
+        matches!(self, Self::Dynamic(_))
+    }
+
+    /// Type id of a static component, None for dynamic components
+    pub fn type_id(&self) -> Option<TypeId> {
+        match self {
+            Self::Static(type_id) => Some(*type_id),
+            Self::Dynamic(_) => None,
+        }
     }
 }
 
 impl From<TypeId> for ComponentId {
     fn from(value: TypeId) -> Self {
-        Self(value)
+        Self::Static(value)
     }
 }
+
 pub trait Component {
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
@@ -0,0 +1,92 @@
+//! Dynamic components
+//!
+//! Components which are not Rust types, they are registered by name at runtime
+//! and carry a json value, useful for scripting and modding.
+
+use roast2d_derive::Component;
+pub use serde_json::Value;
+
+use crate::errors::Error;
+
+/// Kind of a field in dynamic component
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+    Any,
+}
+
+impl FieldKind {
+    pub fn matches(self, value: &Value) -> bool {
+        match self {
+            Self::Bool => value.is_boolean(),
+            Self::Number => value.is_number(),
+            Self::String => value.is_string(),
+            Self::Array => value.is_array(),
+            Self::Object => value.is_object(),
+            Self::Any => true,
+        }
+    }
+}
+
+/// Schema of a dynamic component
+///
+/// The value of component must be an object which contains all fields in the schema.
+#[derive(Debug, Clone, Default)]
+pub struct DynamicSchema {
+    fields: Vec<(String, FieldKind)>,
+}
+
+impl DynamicSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a required field
+    pub fn field<S: Into<String>>(mut self, name: S, kind: FieldKind) -> Self {
+        self.fields.push((name.into(), kind));
+        self
+    }
+
+    pub fn fields(&self) -> &[(String, FieldKind)] {
+        &self.fields
+    }
+
+    /// Validate value against the schema
+    pub fn validate(&self, value: &Value) -> Result<(), Error> {
+        if self.fields.is_empty() {
+            return Ok(());
+        }
+        let Some(object) = value.as_object() else {
+            return Err(Error::InvalidComponent("expect an object".to_string()));
+        };
+        for (name, kind) in &self.fields {
+            match object.get(name) {
+                Some(v) if kind.matches(v) => {}
+                Some(_) => {
+                    return Err(Error::InvalidComponent(format!(
+                        "field {name} expect {kind:?}"
+                    )))
+                }
+                None => return Err(Error::InvalidComponent(format!("missing field {name}"))),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Registered dynamic component
+#[derive(Debug, Clone)]
+pub struct DynamicComponentInfo {
+    pub name: String,
+    pub schema: DynamicSchema,
+}
+
+/// Storage of a dynamic component value
+#[derive(Component, Debug, Clone)]
+pub(crate) struct DynamicComponent {
+    pub(crate) value: Value,
+}
@@ -1,10 +1,13 @@
-use std::any::type_name;
+use std::any::{type_name, Any};
 
 use crate::errors::Error;
 
 use super::{
     component::{Component, ComponentId},
+    dynamic::{DynamicComponent, Value},
     entity::Ent,
+    name::Name,
+    relation::Relation,
     unsafe_world_ref::UnsafeWorldRef,
 };
 
@@ -33,6 +36,18 @@ impl<'w> EntRef<'w> {
             .and_then(|b| b.as_any().downcast_ref())
             .ok_or(Error::NoComponent)
     }
+
+    /// Whether the entity has the tag
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let w = unsafe { self.world_ref.as_ref() };
+        w.has_tag(self.ent, tag)
+    }
+
+    /// Get value of a dynamic component
+    pub fn get_dynamic(&'w self, id: &ComponentId) -> Result<&'w Value, Error> {
+        let w = unsafe { self.world_ref.as_ref() };
+        w.get_dynamic(self.ent, id)
+    }
 }
 
 /// Entity ref
@@ -53,6 +68,9 @@ impl<'w> EntMut<'w> {
 
     pub fn add<T: Component + 'static>(&mut self, component: T) -> &mut Self {
         let w = unsafe { self.world_ref.as_mut() };
+        if let Some(name) = (&component as &dyn Any).downcast_ref::<Name>() {
+            w.index_name(self.ent, name.as_str());
+        }
         if w.storage
             .entry(ComponentId::of::<T>())
             .or_default()
@@ -66,6 +84,9 @@ impl<'w> EntMut<'w> {
 
     pub fn remove<T: Component + 'static>(&mut self) -> &mut Self {
         let w = unsafe { self.world_ref.as_mut() };
+        if ComponentId::of::<T>().is::<Name>() {
+            w.unindex_name(self.ent);
+        }
         w.storage
             .entry(ComponentId::of::<T>())
             .or_default()
@@ -92,4 +113,74 @@ impl<'w> EntMut<'w> {
             .and_then(|b| b.as_any_mut().downcast_mut())
             .ok_or(Error::NoComponent)
     }
+
+    /// Add a dynamic component, the value is validated against the registered schema
+    pub fn add_dynamic(&mut self, id: &ComponentId, value: Value) -> Result<&mut Self, Error> {
+        let w = unsafe { self.world_ref.as_mut() };
+        let info = w.get_dynamic_component_info(id).ok_or(Error::NoComponent)?;
+        info.schema.validate(&value)?;
+        if w.has_component(self.ent, id) {
+            let name = info.name.clone();
+            return Err(Error::ComponentExisted(name));
+        }
+        w.storage
+            .entry(id.clone())
+            .or_default()
+            .insert(self.ent, Box::new(DynamicComponent { value }));
+        Ok(self)
+    }
+
+    /// Remove a dynamic component
+    pub fn remove_dynamic(&mut self, id: &ComponentId) -> &mut Self {
+        let w = unsafe { self.world_ref.as_mut() };
+        if let Some(store) = w.storage.get_mut(id) {
+            store.remove(&self.ent);
+        }
+        self
+    }
+
+    /// Get value of a dynamic component
+    pub fn get_dynamic(&self, id: &ComponentId) -> Result<&Value, Error> {
+        let w = unsafe { self.world_ref.as_ref() };
+        w.get_dynamic(self.ent, id)
+    }
+
+    /// Get mutable value of a dynamic component, the schema is not checked on mutation
+    pub fn get_dynamic_mut(&mut self, id: &ComponentId) -> Result<&mut Value, Error> {
+        let w = unsafe { self.world_ref.as_mut() };
+        w.get_dynamic_mut(self.ent, id)
+    }
+
+    /// Add a tag
+    pub fn tag<S: Into<String>>(&mut self, tag: S) -> &mut Self {
+        let w = unsafe { self.world_ref.as_mut() };
+        w.add_tag(self.ent, tag).expect("entity");
+        self
+    }
+
+    /// Remove a tag
+    pub fn untag(&mut self, tag: &str) -> &mut Self {
+        let w = unsafe { self.world_ref.as_mut() };
+        w.remove_tag(self.ent, tag);
+        self
+    }
+
+    /// Whether the entity has the tag
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let w = unsafe { self.world_ref.as_ref() };
+        w.has_tag(self.ent, tag)
+    }
+
+    /// Add a relation to target
+    pub fn relate<R: Relation>(&mut self, target: Ent) -> &mut Self {
+        let w = unsafe { self.world_ref.as_mut() };
+        if w.relate::<R>(self.ent, target).is_err() {
+            panic!(
+                "Relation target not exist {} {:?}",
+                type_name::<R>(),
+                target
+            );
+        }
+        self
+    }
 }
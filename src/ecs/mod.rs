@@ -1,6 +1,9 @@
 pub mod component;
+pub mod dynamic;
 pub mod entity;
 pub mod entity_ref;
+pub mod name;
+pub mod relation;
 pub mod resource;
 pub mod unsafe_world_ref;
 pub mod world;
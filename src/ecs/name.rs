@@ -0,0 +1,38 @@
+use std::fmt;
+
+use roast2d_derive::Component;
+
+/// Name of an entity
+///
+/// Names are indexed by the world, use `World::find_by_name` to lookup an entity.
+/// Prefer `World::set_name` to rename an entity so the index is kept in sync.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Name(String);
+
+impl Name {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self(name.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for Name {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<String> for Name {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
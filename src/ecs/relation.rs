@@ -0,0 +1,78 @@
+use hashbrown::{HashMap, HashSet};
+
+use super::entity::Ent;
+
+/// Relation
+///
+/// A typed edge between two entities, implement it on a marker type to define a new kind.
+/// Relations are queryable in both directions and removed when either entity is despawned.
+pub trait Relation: 'static {}
+
+/// The source entity is owned by the target, e.g. projectile -> shooter
+pub struct OwnedBy;
+
+impl Relation for OwnedBy {}
+
+/// The source entity targets the target entity
+pub struct Targets;
+
+impl Relation for Targets {}
+
+/// The source entity is riding on the target entity, e.g. player -> moving platform
+pub struct RidingOn;
+
+impl Relation for RidingOn {}
+
+/// Edges of one relation kind
+#[derive(Default)]
+pub(crate) struct RelationStorage {
+    /// source -> targets
+    targets: HashMap<Ent, HashSet<Ent>>,
+    /// target -> sources
+    sources: HashMap<Ent, HashSet<Ent>>,
+}
+
+impl RelationStorage {
+    pub(crate) fn insert(&mut self, source: Ent, target: Ent) {
+        self.targets.entry(source).or_default().insert(target);
+        self.sources.entry(target).or_default().insert(source);
+    }
+
+    pub(crate) fn remove(&mut self, source: Ent, target: Ent) {
+        remove_edge(&mut self.targets, source, target);
+        remove_edge(&mut self.sources, target, source);
+    }
+
+    pub(crate) fn contains(&self, source: Ent, target: Ent) -> bool {
+        self.targets
+            .get(&source)
+            .is_some_and(|targets| targets.contains(&target))
+    }
+
+    pub(crate) fn targets(&self, source: Ent) -> impl Iterator<Item = &Ent> {
+        self.targets.get(&source).into_iter().flatten()
+    }
+
+    pub(crate) fn sources(&self, target: Ent) -> impl Iterator<Item = &Ent> {
+        self.sources.get(&target).into_iter().flatten()
+    }
+
+    /// Remove all edges of the entity
+    pub(crate) fn remove_ent(&mut self, ent: Ent) {
+        for target in self.targets.remove(&ent).into_iter().flatten() {
+            remove_edge(&mut self.sources, target, ent);
+        }
+        for source in self.sources.remove(&ent).into_iter().flatten() {
+            remove_edge(&mut self.targets, source, ent);
+        }
+    }
+}
+
+fn remove_edge(edges: &mut HashMap<Ent, HashSet<Ent>>, from: Ent, to: Ent) {
+    if let Some(set) = edges.get_mut(&from) {
+        set.remove(&to);
+        if set.is_empty() {
+            edges.remove(&from);
+        }
+    }
+}
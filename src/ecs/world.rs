@@ -5,7 +5,10 @@ use crate::{ecs::entity::Ent, errors::Error};
 
 use super::{
     component::{Component, ComponentId},
+    dynamic::{DynamicComponent, DynamicComponentInfo, DynamicSchema, Value},
     entity_ref::{EntMut, EntRef},
+    name::Name,
+    relation::{Relation, RelationStorage},
     resource::Resource,
     unsafe_world_ref::UnsafeWorldRef,
 };
@@ -23,6 +26,14 @@ pub struct World {
     resources: HashMap<ComponentId, Box<dyn Resource>>,
     /// Component by name
     component_by_name: HashMap<String, ComponentId>,
+    /// Dynamic components, indexed by the dynamic id
+    dynamic_components: Vec<DynamicComponentInfo>,
+    /// Entities by name, in the order of naming
+    names: HashMap<String, Vec<Ent>>,
+    /// Entities by tag
+    tags: HashMap<String, HashSet<Ent>>,
+    /// Relations by kind
+    relations: HashMap<ComponentId, RelationStorage>,
 }
 
 impl World {
@@ -43,6 +54,60 @@ impl World {
         self.component_by_name.get(name).cloned()
     }
 
+    /// Register a component at runtime, the component is stored as a json value
+    pub fn register_dynamic_component<S: Into<String>>(
+        &mut self,
+        name: S,
+        schema: DynamicSchema,
+    ) -> Result<ComponentId, Error> {
+        let name = name.into();
+        if self.component_by_name.contains_key(&name) {
+            return Err(Error::ComponentExisted(name));
+        }
+        let component_id = ComponentId::Dynamic(self.dynamic_components.len() as u32);
+        self.dynamic_components.push(DynamicComponentInfo {
+            name: name.clone(),
+            schema,
+        });
+        self.component_by_name.insert(name, component_id.clone());
+        let _ = self
+            .storage
+            .try_insert(component_id.clone(), Default::default());
+        Ok(component_id)
+    }
+
+    /// Get info of a dynamic component
+    pub fn get_dynamic_component_info(&self, id: &ComponentId) -> Option<&DynamicComponentInfo> {
+        match id {
+            ComponentId::Dynamic(index) => self.dynamic_components.get(*index as usize),
+            ComponentId::Static(_) => None,
+        }
+    }
+
+    pub(crate) fn get_dynamic(&self, ent: Ent, id: &ComponentId) -> Result<&Value, Error> {
+        self.storage
+            .get(id)
+            .ok_or(Error::NoComponent)?
+            .get(&ent)
+            .and_then(|b| b.as_any().downcast_ref::<DynamicComponent>())
+            .map(|c| &c.value)
+            .ok_or(Error::NoComponent)
+    }
+
+    pub(crate) fn get_dynamic_mut(
+        &mut self,
+        ent: Ent,
+        id: &ComponentId,
+    ) -> Result<&mut Value, Error> {
+        self.storage
+            .get_mut(id)
+            .ok_or(Error::NoComponent)?
+            .get_mut(&ent)
+            .and_then(|b| b.as_any_mut().downcast_mut::<DynamicComponent>())
+            .map(|c| &mut c.value)
+            .ok_or(Error::NoComponent)
+    }
+
     fn to_unsafe_world_ref(&self) -> UnsafeWorldRef {
         UnsafeWorldRef::new_readonly(self)
     }
@@ -195,6 +260,21 @@ impl World {
             .expect("No component, make sure init_component first")
     }
 
+    /// Iterate component by id, works for both static and dynamic components
+    pub fn iter_by_id(&self, component_id: &ComponentId) -> impl Iterator<Item = &Ent> {
+        self.storage
+            .get(component_id)
+            .into_iter()
+            .flat_map(|v| v.keys())
+    }
+
+    /// Whether the entity has the component
+    pub fn has_component(&self, ent: Ent, component_id: &ComponentId) -> bool {
+        self.storage
+            .get(component_id)
+            .is_some_and(|v| v.contains_key(&ent))
+    }
+
     /// Iterate component
     pub fn iter_ref_by<T: Component + 'static>(&self) -> impl Iterator<Item = EntRef> {
         let component_id = ComponentId::of::<T>();
@@ -223,6 +303,122 @@ impl World {
             .expect("No component, make sure init_component first")
     }
 
+    /// Find an entity by it's name, the last named one if entities share the name
+    pub fn find_by_name(&self, name: &str) -> Option<Ent> {
+        // the name component can be replaced through get_mut, double check it
+        self.names.get(name)?.iter().rev().copied().find(|ent| {
+            self.get_component::<Name>(*ent)
+                .is_some_and(|n| n.as_str() == name)
+        })
+    }
+
+    /// Set name of an entity, replace the previous name
+    pub fn set_name<N: Into<Name>>(&mut self, ent: Ent, name: N) -> Result<(), Error> {
+        let mut ent_mut = self.get_mut(ent)?;
+        ent_mut.remove::<Name>();
+        ent_mut.add(name.into());
+        Ok(())
+    }
+
+    pub(crate) fn index_name(&mut self, ent: Ent, name: &str) {
+        let ents = self.names.entry(name.to_string()).or_default();
+        ents.retain(|e| *e != ent);
+        ents.push(ent);
+    }
+
+    pub(crate) fn unindex_name(&mut self, ent: Ent) {
+        let Some(name) = self.get_component::<Name>(ent) else {
+            return;
+        };
+        let name = name.as_str().to_string();
+        if let Some(ents) = self.names.get_mut(&name) {
+            ents.retain(|e| *e != ent);
+            if ents.is_empty() {
+                self.names.remove(&name);
+            }
+        }
+    }
+
+    /// Add a tag to an entity
+    pub fn add_tag<S: Into<String>>(&mut self, ent: Ent, tag: S) -> Result<(), Error> {
+        if !self.entities.contains(&ent) {
+            return Err(Error::NoEntity);
+        }
+        self.tags.entry(tag.into()).or_default().insert(ent);
+        Ok(())
+    }
+
+    /// Remove a tag from an entity
+    pub fn remove_tag(&mut self, ent: Ent, tag: &str) {
+        if let Some(ents) = self.tags.get_mut(tag) {
+            ents.remove(&ent);
+        }
+    }
+
+    /// Whether the entity has the tag
+    pub fn has_tag(&self, ent: Ent, tag: &str) -> bool {
+        self.tags.get(tag).is_some_and(|ents| ents.contains(&ent))
+    }
+
+    /// Iterate entities by tag
+    pub fn iter_by_tag(&self, tag: &str) -> impl Iterator<Item = &Ent> {
+        self.tags.get(tag).into_iter().flatten()
+    }
+
+    /// Add a relation from source to target
+    pub fn relate<R: Relation>(&mut self, source: Ent, target: Ent) -> Result<(), Error> {
+        if !self.entities.contains(&source) || !self.entities.contains(&target) {
+            return Err(Error::NoEntity);
+        }
+        self.relations
+            .entry(ComponentId::of::<R>())
+            .or_default()
+            .insert(source, target);
+        Ok(())
+    }
+
+    /// Remove a relation from source to target
+    pub fn unrelate<R: Relation>(&mut self, source: Ent, target: Ent) {
+        if let Some(relations) = self.relations.get_mut(&ComponentId::of::<R>()) {
+            relations.remove(source, target);
+        }
+    }
+
+    /// Whether source has a relation to target
+    pub fn is_related<R: Relation>(&self, source: Ent, target: Ent) -> bool {
+        self.relations
+            .get(&ComponentId::of::<R>())
+            .is_some_and(|relations| relations.contains(source, target))
+    }
+
+    /// Iterate targets of the source
+    pub fn iter_targets<R: Relation>(&self, source: Ent) -> impl Iterator<Item = &Ent> {
+        self.relations
+            .get(&ComponentId::of::<R>())
+            .into_iter()
+            .flat_map(move |relations| relations.targets(source))
+    }
+
+    /// Iterate sources which relate to the target
+    pub fn iter_sources<R: Relation>(&self, target: Ent) -> impl Iterator<Item = &Ent> {
+        self.relations
+            .get(&ComponentId::of::<R>())
+            .into_iter()
+            .flat_map(move |relations| relations.sources(target))
+    }
+
+    /// Get the target of source, useful for one-to-one relations like `OwnedBy`
+    pub fn target<R: Relation>(&self, source: Ent) -> Option<Ent> {
+        self.iter_targets::<R>(source).next().cloned()
+    }
+
+    fn get_component<T: Component + 'static>(&self, ent: Ent) -> Option<&T> {
+        self.storage
+            .get(&ComponentId::of::<T>())?
+            .get(&ent)
+            .and_then(|b| b.as_any().downcast_ref())
+    }
+
     /// Spawn a new entity
     pub fn spawn(&mut self) -> EntMut {
         let index = self.unique_id;
@@ -233,6 +429,13 @@ impl World {
     }
 
     pub fn despawn(&mut self, ent: Ent) {
+        self.unindex_name(ent);
+        for ents in self.tags.values_mut() {
+            ents.remove(&ent);
+        }
+        for relations in self.relations.values_mut() {
+            relations.remove_ent(ent);
+        }
         self.entities.remove(&ent);
         for component_store in self.storage.values_mut() {
             component_store.remove(&ent);
@@ -243,6 +446,9 @@ impl World {
     pub fn clear_entities(&mut self) {
         self.entities.clear();
         self.storage.clear();
+        self.names.clear();
+        self.tags.clear();
+        self.relations.clear();
     }
 
     /// Remove entities and resources
@@ -251,3 +457,137 @@ impl World {
         self.resources.clear();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use roast2d_derive::Component;
+
+    use serde_json::json;
+
+    use super::{Name, World};
+    use crate::ecs::component::ComponentId;
+    use crate::ecs::dynamic::{DynamicSchema, FieldKind};
+    use crate::ecs::relation::{OwnedBy, Targets};
+
+    #[derive(Component)]
+    struct Door;
+
+    #[test]
+    fn test_find_by_name() {
+        let mut w = World::default();
+        let player = w.spawn().add(Name::new("player")).id();
+        let door = w.spawn().add(Door).id();
+        assert_eq!(w.find_by_name("player"), Some(player));
+        assert_eq!(w.find_by_name("door"), None);
+
+        w.set_name(door, "door").unwrap();
+        assert_eq!(w.find_by_name("door"), Some(door));
+        w.set_name(door, "exit").unwrap();
+        assert_eq!(w.find_by_name("door"), None);
+        assert_eq!(w.find_by_name("exit"), Some(door));
+
+        w.despawn(player);
+        assert_eq!(w.find_by_name("player"), None);
+    }
+
+    #[test]
+    fn test_find_by_shared_name() {
+        let mut w = World::default();
+        let e1 = w.spawn().add(Name::new("enemy")).id();
+        let e2 = w.spawn().add(Name::new("enemy")).id();
+        assert_eq!(w.find_by_name("enemy"), Some(e2));
+
+        // the other holder is found after one is despawned
+        w.despawn(e2);
+        assert_eq!(w.find_by_name("enemy"), Some(e1));
+        let e3 = w.spawn().add(Name::new("enemy")).id();
+        w.despawn(e1);
+        assert_eq!(w.find_by_name("enemy"), Some(e3));
+        w.despawn(e3);
+        assert_eq!(w.find_by_name("enemy"), None);
+        assert!(w.names.is_empty());
+    }
+
+    #[test]
+    fn test_iter_by_tag() {
+        let mut w = World::default();
+        let e1 = w.spawn().tag("enemy").id();
+        let e2 = w.spawn().tag("enemy").tag("boss").id();
+        let mut enemies: Vec<_> = w.iter_by_tag("enemy").cloned().collect();
+        enemies.sort_by_key(|e| e.index);
+        assert_eq!(enemies, vec![e1, e2]);
+        assert!(w.has_tag(e2, "boss"));
+        assert_eq!(w.iter_by_tag("npc").count(), 0);
+
+        w.despawn(e2);
+        assert_eq!(w.iter_by_tag("enemy").collect::<Vec<_>>(), vec![&e1]);
+        assert_eq!(w.iter_by_tag("boss").count(), 0);
+    }
+
+    #[test]
+    fn test_relations() {
+        let mut w = World::default();
+        let shooter = w.spawn().id();
+        let bullet1 = w.spawn().relate::<OwnedBy>(shooter).id();
+        let bullet2 = w.spawn().relate::<OwnedBy>(shooter).id();
+        let enemy = w.spawn().id();
+        w.relate::<Targets>(bullet1, enemy).unwrap();
+
+        assert_eq!(w.target::<OwnedBy>(bullet1), Some(shooter));
+        assert!(w.is_related::<Targets>(bullet1, enemy));
+        assert!(!w.is_related::<OwnedBy>(bullet1, enemy));
+        assert_eq!(w.iter_sources::<OwnedBy>(shooter).count(), 2);
+        assert_eq!(
+            w.iter_sources::<Targets>(enemy).collect::<Vec<_>>(),
+            vec![&bullet1]
+        );
+
+        // despawn the target
+        w.despawn(enemy);
+        assert_eq!(w.target::<Targets>(bullet1), None);
+
+        // despawn the source
+        w.despawn(bullet2);
+        assert_eq!(
+            w.iter_sources::<OwnedBy>(shooter).collect::<Vec<_>>(),
+            vec![&bullet1]
+        );
+
+        w.unrelate::<OwnedBy>(bullet1, shooter);
+        assert_eq!(w.target::<OwnedBy>(bullet1), None);
+        assert!(w.relate::<OwnedBy>(bullet1, bullet2).is_err());
+    }
+
+    #[test]
+    fn test_dynamic_component() {
+        let mut w = World::default();
+        let schema = DynamicSchema::new()
+            .field("speed", FieldKind::Number)
+            .field("script", FieldKind::String);
+        let id = w.register_dynamic_component("Mover", schema).unwrap();
+        assert!(id.is_dynamic());
+        assert_eq!(id.type_id(), None);
+        assert_eq!(
+            ComponentId::of::<Door>().type_id(),
+            Some(std::any::TypeId::of::<Door>())
+        );
+        assert_eq!(w.get_component_id_by_name("Mover"), Some(id.clone()));
+        assert!(w
+            .register_dynamic_component("Mover", DynamicSchema::new())
+            .is_err());
+
+        let ent = w.spawn().id();
+        let mut ent_mut = w.get_mut(ent).unwrap();
+        assert!(ent_mut.add_dynamic(&id, json!({"speed": "fast"})).is_err());
+        ent_mut
+            .add_dynamic(&id, json!({"speed": 2.0, "script": "mover.lua"}))
+            .unwrap();
+        ent_mut.get_dynamic_mut(&id).unwrap()["speed"] = json!(4.0);
+
+        assert_eq!(w.iter_by_id(&id).collect::<Vec<_>>(), vec![&ent]);
+        assert_eq!(w.get(ent).unwrap().get_dynamic(&id).unwrap()["speed"], 4.0);
+
+        w.get_mut(ent).unwrap().remove_dynamic(&id);
+        assert!(!w.has_component(ent, &id));
+    }
+}
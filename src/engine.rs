@@ -1,5 +1,6 @@
 use std::{
     cell::{RefCell, UnsafeCell},
+    f32::consts::{FRAC_PI_2, PI, TAU},
     path::Path,
     sync::OnceLock,
 };
@@ -11,18 +12,27 @@ use crate::{
     asset::{Asset, AssetManager, AssetType, FetchedTask},
     camera::Camera,
     color::Color,
+    draw_queue::DrawKey,
     ecs::world::World,
     font::{Font, Text},
     handle::Handle,
     input::InputState,
+    layer::{RenderLayer, RenderLayers},
+    lifetime::{despawn_expired, init_lifetime, tick_lifetimes},
     platform::Platform,
-    render::{Render, ScaleMode},
+    render::{BlendMode, CameraTransform, Render, ScaleMode},
+    shape::{self, CIRCLE_TEXTURE_SIZE},
     sprite::Sprite,
     text_cache::{init_text_cache, TextCache},
+    types::Rect,
 };
 
+pub use crate::perf::Perf;
+
 /// Default texture
 static DEFAULT_TEXTURE: OnceLock<Handle> = OnceLock::new();
+/// Circle texture, used to draw filled circles and round corners
+static CIRCLE_TEXTURE: OnceLock<Handle> = OnceLock::new();
 /// Max tick
 const ENGINE_MAX_TICK: f32 = 100.0;
 /// Default font
@@ -38,9 +48,31 @@ fn default_texture(g: &mut Engine) -> Handle {
                 asset_type: AssetType::Texture,
                 bytes: None,
             });
-            g.with_platform(|p| {
-                p.create_texture(handle.clone(), data, size);
+            g.assets
+                .track_memory(handle.id(), AssetType::Texture, data.len());
+            g.render
+                .borrow_mut()
+                .create_texture(handle.clone(), data, size);
+            handle
+        })
+        .clone()
+}
+
+/// get circle texture
+fn circle_texture(g: &mut Engine) -> Handle {
+    CIRCLE_TEXTURE
+        .get_or_init(|| {
+            let data = shape::circle_pixels(CIRCLE_TEXTURE_SIZE);
+            let size = UVec2::splat(CIRCLE_TEXTURE_SIZE);
+            let handle = g.assets.insert(Asset {
+                asset_type: AssetType::Texture,
+                bytes: None,
             });
+            g.assets
+                .track_memory(handle.id(), AssetType::Texture, data.len());
+            g.render
+                .borrow_mut()
+                .create_texture(handle.clone(), data, size);
             handle
         })
         .clone()
@@ -55,20 +87,13 @@ pub trait Scene {
     fn update(&mut self, g: &mut Engine, w: &mut World);
 
     // Draw scene per frame, use it to draw entities or Hud, you probably want to call scene_base_draw if you override this function.
+    // Called once by each camera, `Engine::active_camera` tells which camera is drawing.
     fn draw(&mut self, g: &mut Engine, w: &mut World);
 
     // Called when cleanup scene, release assets and resources.
     fn cleanup(&mut self, _g: &mut Engine, _w: &mut World);
 }
 
-#[derive(Default, Debug)]
-pub struct Perf {
-    pub entities: usize,
-    pub update: f32,
-    pub draw: f32,
-    pub total: f32,
-}
-
 pub struct Engine {
     // The real time in seconds since program start
     pub time_real: f32,
@@ -107,8 +132,12 @@ pub struct Engine {
     scene_next: Option<Box<dyn Scene>>,
     pub(crate) world: UnsafeCell<World>,
 
-    // camera
-    pub(crate) camera: Camera,
+    // cameras, the first one is the main camera
+    pub(crate) cameras: Vec<Camera>,
+    // index of the camera being updated or drawn
+    active_camera: usize,
+    // render layers
+    layers: RenderLayers,
     // render
     pub(crate) render: RefCell<Render>,
     // AssetsManager
@@ -125,7 +154,9 @@ impl Engine {
             frame: 0.0,
             bounds: None,
             gravity: 0.0,
-            camera: Camera::default(),
+            cameras: vec![Camera::default()],
+            active_camera: 0,
+            layers: RenderLayers::default(),
             perf: Perf::default(),
             is_running: false,
             is_window_resized: false,
@@ -138,6 +169,15 @@ impl Engine {
         }
     }
 
+    /// Create an initialized engine without a window, draws are dropped,
+    /// useful for tests and tools. Assets are fetched by calling `handle_assets`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn headless() -> Self {
+        let mut engine = Self::new(Box::new(crate::platform::HeadlessPlatform::new()));
+        engine.init(|_g, _w| {});
+        engine
+    }
+
     pub(crate) fn with_platform<R, F: FnOnce(&mut dyn Platform) -> R>(&mut self, f: F) -> R {
         let mut r = self.render.borrow_mut();
         f(r.platform.as_mut())
@@ -179,14 +219,19 @@ impl Engine {
     /// ```
     pub fn draw_text(&mut self, mut text: Text, pos: Vec2, anchor: Vec2, angle: Option<f32>) {
         let w = unsafe { self.borrow_world() };
-        let (handle, size) = match w
-            .get_resource::<TextCache>()
-            .expect("text cache")
+        let cache = w.get_resource_mut::<TextCache>().expect("text cache");
+        let cached = cache
             .get(&text)
-        {
-            Some((handle, size)) => (handle.clone(), *size),
+            .map(|(handle, size)| (handle.clone(), *size));
+        let (handle, size) = match cached {
+            Some(cached) => {
+                cache.hits += 1;
+                cached
+            }
             None => {
-                // render text texture
+                cache.misses += 1;
+                // render text texture, the text is rendered with default font if the font is not ready,
+                // it is dropped from the cache when the font is added
                 text.scale *= 2.0;
                 let (handle, size) = self.create_text_texture(w, &text);
                 let size = size / 2;
@@ -201,6 +246,64 @@ impl Engine {
         self.draw_image(&sprite, pos, None, angle);
     }
 
+    /// Create texture from RGBA pixels
+    pub fn create_texture(&mut self, data: Vec<u8>, size: UVec2) -> Handle {
+        let handle = self.assets.insert(Asset {
+            asset_type: AssetType::Texture,
+            bytes: None,
+        });
+        self.assets
+            .track_memory(handle.id(), AssetType::Texture, data.len());
+        self.render
+            .borrow_mut()
+            .create_texture(handle.clone(), data, size);
+        handle
+    }
+
+    /// Create an offscreen render target, draw into it with `draw_to_target`,
+    /// the handle can be drawn by a `Sprite` like other textures
+    pub fn create_render_target(&mut self, size: UVec2) -> Handle {
+        let handle = self.assets.insert(Asset {
+            asset_type: AssetType::Texture,
+            bytes: None,
+        });
+        self.assets.track_memory(
+            handle.id(),
+            AssetType::Texture,
+            (size.x * size.y * 4) as usize,
+        );
+        self.render
+            .borrow_mut()
+            .create_render_target(handle.clone(), size);
+        handle
+    }
+
+    /// Draw into a render target instead of the screen, the target is cleared with `clear` first if given.
+    /// Draws are flushed into the target at the end of the scope, so the target can be drawn in the same frame.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use roast2d::prelude::*;
+    /// # fn draw_minimap(g: &mut Engine, w: &mut World) {}
+    /// # fn draw(g: &mut Engine, w: &mut World, minimap: &Handle) {
+    ///   g.draw_to_target(minimap, Some(BLACK), |g| draw_minimap(g, w));
+    ///   let sprite = Sprite::new(minimap.clone(), UVec2::new(64, 64));
+    ///   g.draw_image(&sprite, Vec2::new(32.0, 32.0), None, None);
+    /// # }
+    /// ```
+    pub fn draw_to_target<R>(
+        &mut self,
+        target: &Handle,
+        clear: Option<Color>,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        self.render.borrow_mut().begin_target(target, clear);
+        let r = f(self);
+        self.render.borrow_mut().end_target();
+        r
+    }
+
     /// Create text texture
     pub fn create_text_texture(&mut self, w: &mut World, text: &Text) -> (Handle, UVec2) {
         let text_cache = w
@@ -214,6 +317,11 @@ impl Engine {
             .render
             .borrow_mut()
             .create_text_texture(text_cache, handle.clone(), text);
+        self.assets.track_memory(
+            handle.id(),
+            AssetType::Texture,
+            (size.x * size.y * 4) as usize,
+        );
         (handle, size)
     }
 
@@ -231,17 +339,19 @@ impl Engine {
     /// # Examples
     ///
     /// ```
-    /// # use roast2d::prelude::*;
-    /// // draw a blue rectangle
-    /// # fn draw(g: &mut Engine, w: &mut World, ent: Ent, viewport: Vec2) {
-    ///   g.draw_rect(
-    ///       Vec2::splat(40.0),
-    ///       Vec2::new(50.0, 100.0) + viewport,
-    ///       BLUE,
-    ///       None,
-    ///       None,
-    ///       None,
-    ///   );
+    /// # use roast2d::{layer, prelude::*};
+    /// // draw a blue rectangle in the world
+    /// # fn draw(g: &mut Engine, w: &mut World, ent: Ent) {
+    ///   g.with_layer(layer::WORLD, |g| {
+    ///       g.draw_rect(
+    ///           Vec2::splat(40.0),
+    ///           Vec2::new(50.0, 100.0),
+    ///           BLUE,
+    ///           None,
+    ///           None,
+    ///           None,
+    ///       );
+    ///   });
     /// # }
     /// ```
     pub fn draw_rect(
@@ -264,6 +374,273 @@ impl Engine {
             .draw_image(&image, pos, scale, angle);
     }
 
+    /// Draw a nine-slice sprite into rect, corners keep their size and
+    /// edges and center are stretched or tiled by `Sprite::nine_slice`.
+    /// Sprite without `nine_slice` is stretched, flips and anchor are ignored
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use roast2d::prelude::*;
+    /// # fn draw(g: &mut Engine, panel: &Sprite) {
+    ///   let panel = panel.clone().with_nine_slice(NineSlice::uniform(4.0));
+    ///   let rect = Rect {
+    ///       min: Vec2::new(10.0, 10.0),
+    ///       max: Vec2::new(110.0, 50.0),
+    ///   };
+    ///   g.draw_nine_slice(&panel, rect);
+    /// # }
+    /// ```
+    pub fn draw_nine_slice(&mut self, sprite: &Sprite, rect: Rect) {
+        let src = sprite.src.clone().unwrap_or(Rect {
+            min: Vec2::ZERO,
+            max: sprite.sizef(),
+        });
+        let slice = sprite.nine_slice.unwrap_or_default();
+        let mut render = self.render.borrow_mut();
+        for (src, dst) in slice.patches(&src, &rect) {
+            render.draw(
+                &sprite.texture,
+                sprite.color,
+                sprite.blend,
+                Some(src),
+                dst,
+                None,
+                false,
+                false,
+            );
+        }
+    }
+
+    /// Draw a rect with the default texture
+    fn draw_shape_rect(&mut self, dst: Rect, angle: Option<f32>, color: Color) {
+        let texture = default_texture(self);
+        self.render.borrow_mut().draw(
+            &texture,
+            color,
+            BlendMode::Alpha,
+            None,
+            dst,
+            angle,
+            false,
+            false,
+        );
+    }
+
+    /// Draw a rectangle outline, the outline is inside the rectangle
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Size
+    /// * `pos` - Position
+    /// * `thickness` - Thickness of the outline
+    /// * `color` - Color
+    /// * `anchor` - Anchor, default is (0.5, 0.5)
+    pub fn draw_rect_outline(
+        &mut self,
+        size: Vec2,
+        pos: Vec2,
+        thickness: f32,
+        color: Color,
+        anchor: Option<Vec2>,
+    ) {
+        let anchor = anchor.unwrap_or(Vec2::splat(0.5));
+        let min = pos - size * anchor;
+        let max = min + size;
+        let t = thickness.min(size.min_element() * 0.5);
+        let edges = [
+            (min, Vec2::new(max.x, min.y + t)),
+            (Vec2::new(min.x, max.y - t), max),
+            (Vec2::new(min.x, min.y + t), Vec2::new(min.x + t, max.y - t)),
+            (Vec2::new(max.x - t, min.y + t), Vec2::new(max.x, max.y - t)),
+        ];
+        for (min, max) in edges {
+            self.draw_shape_rect(Rect { min, max }, None, color);
+        }
+    }
+
+    /// Draw a line
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use roast2d::{layer, prelude::*};
+    /// // draw a laser beam in the world
+    /// # fn draw(g: &mut Engine, from: Vec2, to: Vec2) {
+    ///   g.with_layer(layer::WORLD, |g| g.draw_line(from, to, 2.0, RED));
+    /// # }
+    /// ```
+    pub fn draw_line(&mut self, from: Vec2, to: Vec2, thickness: f32, color: Color) {
+        let (dst, angle) = shape::line_rect(from, to, thickness, 0.0);
+        self.draw_shape_rect(dst, Some(angle), color);
+    }
+
+    /// Draw connected lines, joints are filled
+    fn draw_polyline(&mut self, points: &[Vec2], thickness: f32, color: Color) {
+        for line in points.windows(2) {
+            let (dst, angle) = shape::line_rect(line[0], line[1], thickness, thickness * 0.5);
+            self.draw_shape_rect(dst, Some(angle), color);
+        }
+    }
+
+    /// Draw a filled circle
+    pub fn draw_circle(&mut self, center: Vec2, radius: f32, color: Color) {
+        self.draw_ellipse(center, Vec2::splat(radius), None, color);
+    }
+
+    /// Draw a circle outline
+    pub fn draw_circle_outline(&mut self, center: Vec2, radius: f32, thickness: f32, color: Color) {
+        self.draw_ellipse_outline(center, Vec2::splat(radius), None, thickness, color);
+    }
+
+    /// Draw a filled ellipse
+    pub fn draw_ellipse(&mut self, center: Vec2, radius: Vec2, angle: Option<f32>, color: Color) {
+        let texture = circle_texture(self);
+        let dst = Rect {
+            min: center - radius,
+            max: center + radius,
+        };
+        self.render.borrow_mut().draw(
+            &texture,
+            color,
+            BlendMode::Alpha,
+            None,
+            dst,
+            angle,
+            false,
+            false,
+        );
+    }
+
+    /// Draw an ellipse outline
+    pub fn draw_ellipse_outline(
+        &mut self,
+        center: Vec2,
+        radius: Vec2,
+        angle: Option<f32>,
+        thickness: f32,
+        color: Color,
+    ) {
+        let points = shape::ellipse_points(center, radius, angle.unwrap_or_default(), 0.0, TAU);
+        self.draw_polyline(&points, thickness, color);
+    }
+
+    /// Draw a filled polygon, the polygon can be concave
+    pub fn draw_polygon(&mut self, points: &[Vec2], color: Color) {
+        let (view, step) = {
+            let render = self.render.borrow();
+            (render.visible_rect(), render.pixel_size())
+        };
+        for span in shape::polygon_spans(points, step, &view) {
+            self.draw_shape_rect(span, None, color);
+        }
+    }
+
+    /// Draw a polygon outline
+    pub fn draw_polygon_outline(&mut self, points: &[Vec2], thickness: f32, color: Color) {
+        if points.len() < 2 {
+            return;
+        }
+        let mut points = points.to_vec();
+        points.push(points[0]);
+        self.draw_polyline(&points, thickness, color);
+    }
+
+    /// Draw a filled rectangle with round corners
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Size
+    /// * `pos` - Position
+    /// * `radius` - Radius of corners
+    /// * `color` - Color
+    /// * `anchor` - Anchor, default is (0.5, 0.5)
+    pub fn draw_rounded_rect(
+        &mut self,
+        size: Vec2,
+        pos: Vec2,
+        radius: f32,
+        color: Color,
+        anchor: Option<Vec2>,
+    ) {
+        let anchor = anchor.unwrap_or(Vec2::splat(0.5));
+        let min = pos - size * anchor;
+        let max = min + size;
+        let r = radius.clamp(0.0, size.min_element() * 0.5);
+        let rects = [
+            (Vec2::new(min.x + r, min.y), Vec2::new(max.x - r, max.y)),
+            (Vec2::new(min.x, min.y + r), Vec2::new(min.x + r, max.y - r)),
+            (Vec2::new(max.x - r, min.y + r), Vec2::new(max.x, max.y - r)),
+        ];
+        for (min, max) in rects {
+            self.draw_shape_rect(Rect { min, max }, None, color);
+        }
+        if r <= 0.0 {
+            return;
+        }
+        let texture = circle_texture(self);
+        let rect = Rect { min, max };
+        let texture_rect = Rect {
+            min: Vec2::ZERO,
+            max: Vec2::splat(CIRCLE_TEXTURE_SIZE as f32),
+        };
+        let mut render = self.render.borrow_mut();
+        for dir in [
+            Vec2::new(-1.0, -1.0),
+            Vec2::new(1.0, -1.0),
+            Vec2::new(-1.0, 1.0),
+            Vec2::new(1.0, 1.0),
+        ] {
+            let src = shape::corner_rect(&texture_rect, CIRCLE_TEXTURE_SIZE as f32 * 0.5, dir);
+            let dst = shape::corner_rect(&rect, r, dir);
+            render.draw(
+                &texture,
+                color,
+                BlendMode::Alpha,
+                Some(src),
+                dst,
+                None,
+                false,
+                false,
+            );
+        }
+    }
+
+    /// Draw a rectangle outline with round corners, the outline is inside the rectangle
+    pub fn draw_rounded_rect_outline(
+        &mut self,
+        size: Vec2,
+        pos: Vec2,
+        radius: f32,
+        thickness: f32,
+        color: Color,
+        anchor: Option<Vec2>,
+    ) {
+        let anchor = anchor.unwrap_or(Vec2::splat(0.5));
+        let half_t = thickness.min(size.min_element() * 0.5) * 0.5;
+        // the center line of the outline
+        let min = pos - size * anchor + half_t;
+        let max = min + size - half_t * 2.0;
+        let r = (radius - half_t).clamp(0.0, (max - min).min_element() * 0.5);
+        let corners = [
+            (Vec2::new(max.x - r, min.y + r), -FRAC_PI_2),
+            (Vec2::new(max.x - r, max.y - r), 0.0),
+            (Vec2::new(min.x + r, max.y - r), FRAC_PI_2),
+            (Vec2::new(min.x + r, min.y + r), PI),
+        ];
+        let mut points = Vec::new();
+        for (center, start) in corners {
+            points.extend(shape::ellipse_points(
+                center,
+                Vec2::splat(r),
+                0.0,
+                start,
+                start + FRAC_PI_2,
+            ));
+        }
+        self.draw_polygon_outline(&points, thickness, color);
+    }
+
     /// Draw image
     ///
     /// # Arguments
@@ -276,17 +653,14 @@ impl Engine {
     /// # Examples
     ///
     /// ```
-    /// # use roast2d::prelude::*;
-    /// // draw entity's sprite
-    /// # fn draw(g: &mut Engine, w: &mut World, ent: Ent, viewport: Vec2) {
+    /// # use roast2d::{layer, prelude::*};
+    /// // draw entity's sprite in the world
+    /// # fn draw(g: &mut Engine, w: &mut World, ent: Ent) {
     ///   let ent_ref = w.get(ent).unwrap();
     ///   let sprite = ent_ref.get::<Sprite>().unwrap();
-    ///   g.draw_image(
-    ///       &sprite,
-    ///       Vec2::new(50.0, 100.0) + viewport,
-    ///       None,
-    ///       None,
-    ///   );
+    ///   g.with_layer(layer::WORLD, |g| {
+    ///       g.draw_image(&sprite, Vec2::new(50.0, 100.0), None, None);
+    ///   });
     /// # }
     /// ```
     pub fn draw_image(
@@ -301,6 +675,168 @@ impl Engine {
             .draw_image(image, pos, scale, angle);
     }
 
+    /// Set the key of following draws, draws are flushed at the end of frame
+    /// sorted by layer then depth, draws with the same key keep the call order
+    pub fn set_draw_key(&mut self, key: DrawKey) {
+        self.render.borrow_mut().draw_key = key;
+    }
+
+    pub fn draw_key(&self) -> DrawKey {
+        self.render.borrow().draw_key
+    }
+
+    /// Draw with the key, restore the previous key after
+    pub fn with_draw_key<R>(&mut self, key: DrawKey, f: impl FnOnce(&mut Self) -> R) -> R {
+        let prev = self.draw_key();
+        self.set_draw_key(key);
+        let r = f(self);
+        self.set_draw_key(prev);
+        r
+    }
+
+    /// Clip following draws to rect until `pop_clip`, the rect is in view space
+    /// and is intersected with the current clip. Camera zoom and rotation don't apply to clips
+    pub fn push_clip(&mut self, rect: Rect) {
+        self.render.borrow_mut().push_clip(rect);
+    }
+
+    /// Restore the previous clip
+    pub fn pop_clip(&mut self) {
+        self.render.borrow_mut().pop_clip();
+    }
+
+    /// Draw clipped to rect
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use roast2d::prelude::*;
+    /// # fn draw_items(g: &mut Engine, scroll: f32) {}
+    /// # fn draw(g: &mut Engine, scroll: f32) {
+    ///   let list = Rect {
+    ///       min: Vec2::new(10.0, 10.0),
+    ///       max: Vec2::new(110.0, 80.0),
+    ///   };
+    ///   g.with_clip(list, |g| draw_items(g, scroll));
+    /// # }
+    /// ```
+    pub fn with_clip<R>(&mut self, rect: Rect, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.push_clip(rect);
+        let r = f(self);
+        self.pop_clip();
+        r
+    }
+
+    /// Add a render layer, replace the layer with the same name.
+    /// Default layers are `layer::BACKGROUND`, `WORLD`, `FOREGROUND`, `HUD` and `DEBUG`
+    pub fn add_layer(&mut self, layer: RenderLayer) -> Option<RenderLayer> {
+        self.layers.insert(layer)
+    }
+
+    pub fn remove_layer(&mut self, name: &str) -> Option<RenderLayer> {
+        self.layers.remove(name)
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&RenderLayer> {
+        self.layers.get(name)
+    }
+
+    /// Layers ordered by `RenderLayer::order`
+    pub fn layers(&self) -> impl Iterator<Item = &RenderLayer> {
+        self.layers.iter()
+    }
+
+    /// Draw in the layer, draws are sorted by the layer order and positions are in world
+    /// space moved by the camera factor of the layer, no need to subtract the viewport.
+    /// In a layer with camera factor 0 positions are in screen space.
+    /// Return None without calling `f` if the layer is not found
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use roast2d::{layer, prelude::*};
+    /// # fn draw(g: &mut Engine, player_pos: Vec2) {
+    ///   g.with_layer(layer::WORLD, |g| {
+    ///       g.draw_rect(Vec2::splat(8.0), player_pos, BLUE, None, None, None);
+    ///   });
+    ///   g.with_layer(layer::HUD, |g| {
+    ///       g.draw_rect(Vec2::new(100.0, 8.0), Vec2::new(60.0, 10.0), RED, None, None, None);
+    ///   });
+    /// # }
+    /// ```
+    pub fn with_layer<R>(&mut self, name: &str, f: impl FnOnce(&mut Self) -> R) -> Option<R> {
+        let Some(layer) = self.layers.get(name) else {
+            self.layers.warn_missing(name);
+            return None;
+        };
+        let camera = self.camera();
+        let transform =
+            (layer.camera_factor != 0.0 && camera.is_transformed()).then(|| CameraTransform {
+                center: self.view_size() * 0.5,
+                zoom: layer.zoom(camera.zoom),
+                rotation: camera.rotation,
+            });
+        let layer_offset = self.render.borrow().snap_px(layer.offset(self.viewport()));
+        let tint = layer.tint;
+        let key = DrawKey {
+            layer: layer.order,
+            ..self.draw_key()
+        };
+        let prev = {
+            let mut render = self.render.borrow_mut();
+            let prev = (
+                render.draw_key,
+                render.layer_offset,
+                render.tint,
+                render.camera_transform,
+            );
+            render.draw_key = key;
+            render.layer_offset = layer_offset;
+            render.tint = tint;
+            render.camera_transform = transform;
+            prev
+        };
+        let r = f(self);
+        let mut render = self.render.borrow_mut();
+        (
+            render.draw_key,
+            render.layer_offset,
+            render.tint,
+            render.camera_transform,
+        ) = prev;
+        Some(r)
+    }
+
+    /// Camera offset subtracted from draws by the current layer. Code that subtracts
+    /// the viewport itself should subtract `viewport() - layer_offset()` to work in any layer
+    pub fn layer_offset(&self) -> Vec2 {
+        self.render.borrow().layer_offset
+    }
+
+    /// Fill layers that have a clear color
+    fn clear_layers(&mut self) {
+        let clears: Vec<_> = self
+            .layers
+            .iter()
+            .filter_map(|layer| Some((layer.order, layer.clear?)))
+            .collect();
+        let dst = Rect {
+            min: Vec2::ZERO,
+            max: self.view_size(),
+        };
+        for (order, color) in clears {
+            self.with_draw_key(DrawKey::new(order, f32::NEG_INFINITY), |g| {
+                g.draw_shape_rect(dst.clone(), None, color);
+            });
+        }
+    }
+
+    /// Group draws with the same key by texture to reduce texture switches,
+    /// the call order of draws with the same key is not kept. Disabled by default
+    pub fn set_texture_batching(&mut self, enabled: bool) {
+        self.render.borrow_mut().set_batch_by_texture(enabled);
+    }
+
     /// Draw image as tile
     pub fn draw_tile(
         &mut self,
@@ -318,9 +854,9 @@ impl Engine {
         );
     }
 
-    /// View size
+    /// View size, the size of the camera pane when drawing with a camera
     pub fn view_size(&self) -> Vec2 {
-        self.render.borrow().logical_size()
+        self.render.borrow().view_size()
     }
 
     pub fn is_window_resized(&self) -> bool {
@@ -341,14 +877,64 @@ impl Engine {
         self.with_platform(|p| p.now())
     }
 
-    // Input
+    /// Measure a system, the elapsed time is recorded in `perf.systems`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use roast2d::prelude::*;
+    /// # fn update_enemies(g: &mut Engine, w: &mut World) {}
+    /// # fn update(g: &mut Engine, w: &mut World) {
+    ///   g.measure("enemies", |g| update_enemies(g, w));
+    /// # }
+    /// ```
+    pub fn measure<R, F: FnOnce(&mut Engine) -> R>(&mut self, name: &'static str, f: F) -> R {
+        let start = self.now();
+        let r = f(self);
+        let elapsed = self.now() - start;
+        self.perf.add_system_time(name, elapsed);
+        r
+    }
+
+    /// The active camera, it is the main camera except when drawing with other cameras
     pub fn camera(&self) -> &Camera {
-        &self.camera
+        &self.cameras[self.active_camera]
     }
 
-    // Input mut
+    /// The active camera mut
     pub fn camera_mut(&mut self) -> &mut Camera {
-        &mut self.camera
+        &mut self.cameras[self.active_camera]
+    }
+
+    /// Index of the active camera
+    pub fn active_camera(&self) -> usize {
+        self.active_camera
+    }
+
+    /// Add a camera, return the index of the camera.
+    /// The scene is drawn once by each camera, set `Camera::pane` to split the screen
+    pub fn add_camera(&mut self, camera: Camera) -> usize {
+        self.cameras.push(camera);
+        self.cameras.len() - 1
+    }
+
+    /// Remove a camera by index, the main camera can't be removed.
+    /// Indexes of following cameras are shifted
+    pub fn remove_camera(&mut self, index: usize) -> Option<Camera> {
+        if index == 0 || index >= self.cameras.len() {
+            return None;
+        }
+        Some(self.cameras.remove(index))
+    }
+
+    /// All cameras, the first one is the main camera
+    pub fn cameras(&self) -> &[Camera] {
+        &self.cameras
+    }
+
+    /// All cameras mut
+    pub fn cameras_mut(&mut self) -> &mut [Camera] {
+        &mut self.cameras
     }
 
     // Input
@@ -370,6 +956,7 @@ impl Engine {
         self.time_real = self.now();
         // init textcache
         init_text_cache(self, world);
+        init_lifetime(self, world);
         self.init_default_font(world);
 
         setup(self, world);
@@ -381,6 +968,8 @@ impl Engine {
             asset_type: AssetType::Font,
             bytes: None,
         });
+        self.assets
+            .track_memory(handle.id(), AssetType::Font, DEFAULT_FONT_BYTES.len());
         let font =
             Font::from_bytes(DEFAULT_FONT_BYTES.into()).expect("Failed to load default font");
         world
@@ -404,6 +993,11 @@ impl Engine {
 
     pub(crate) fn inner_update(&mut self, w: &mut World) {
         let time_frame_start = self.now();
+        self.perf.begin_frame();
+        self.render.borrow_mut().begin_frame();
+        if let Ok(cache) = w.get_resource_mut::<TextCache>() {
+            cache.reset_stats();
+        }
 
         if self.scene_next.is_some() {
             self.is_running = false;
@@ -413,7 +1007,9 @@ impl Engine {
 
             self.time = 0.;
             self.frame = 0.;
-            self.camera.viewport = Vec2::new(0., 0.);
+            for camera in self.cameras.iter_mut() {
+                camera.viewport = Vec2::new(0., 0.);
+            }
 
             if let Some(mut scene) = self.scene_next.take() {
                 scene.init(self, w);
@@ -429,48 +1025,79 @@ impl Engine {
         self.time += self.tick;
         self.frame += 1.;
 
+        tick_lifetimes(self.tick, w);
         if let Some(mut scene) = self.scene.take() {
             scene.update(self, w);
             self.scene = Some(scene);
         }
+        despawn_expired(w);
         self.perf.entities = w.ents_count();
 
-        // Update camera
-        let camera_follow = self.camera.follow.and_then(|ent_ref| w.get(ent_ref).ok());
-        self.camera.update(
-            self.tick,
-            self.render.borrow().logical_size(),
-            camera_follow,
-            self.bounds,
-        );
+        // Update cameras
+        let screen_size = self.render.borrow().logical_size();
+        for camera in self.cameras.iter_mut() {
+            let pane = camera.pane_rect(screen_size);
+            let camera_follow = camera.follow.and_then(|ent_ref| w.get(ent_ref).ok());
+            camera.update(self.tick, pane.max - pane.min, camera_follow, self.bounds);
+        }
         self.perf.update = self.now() - time_real_now;
 
+        // Draw the scene by each camera
         if let Some(mut scene) = self.scene.take() {
-            scene.draw(self, w);
+            for index in 0..self.cameras.len() {
+                self.active_camera = index;
+                self.render.borrow_mut().pane = self.cameras[index].pane.clone();
+                self.clear_layers();
+                scene.draw(self, w);
+            }
+            self.active_camera = 0;
+            self.render.borrow_mut().pane = None;
             self.scene = Some(scene);
         }
 
+        self.render.borrow_mut().end_frame();
         self.perf.draw = (self.now() - time_real_now) - self.perf.update;
         self.input.clear();
         self.is_window_resized = false;
+        self.collect_perf(w);
         self.perf.total = self.now() - time_frame_start;
+        self.perf.end_frame();
     }
 
-    pub(crate) async fn handle_assets(&mut self) -> Result<()> {
+    fn collect_perf(&mut self, w: &mut World) {
+        {
+            let render = self.render.borrow();
+            self.perf.draw_calls = render.draw_calls;
+            self.perf.culled_draws = render.culled_draws;
+            self.perf.draw_batches = render.draw_batches;
+            self.perf.textures = render.textures_count();
+        }
+        if let Ok(cache) = w.get_resource::<TextCache>() {
+            self.perf.text_cache_size = cache.len();
+            self.perf.text_cache_hit_rate = cache.hit_rate();
+        }
+        self.perf.pending_assets = self.assets.pending_tasks();
+        // the asset manager tracks texture memory, it is a part of the asset memory
+        let memory = self.assets.memory_stats();
+        self.perf.texture_memory = memory.textures;
+        self.perf.asset_memory = memory.total();
+    }
+
+    /// Fetch loaded assets and upload them, called by the platform every frame
+    pub async fn handle_assets(&mut self) -> Result<()> {
         let world = unsafe { self.borrow_world() };
 
-        let tasks = self.assets.fetch().await?;
+        let used = self.render.borrow_mut().take_used_textures();
+        self.assets.mark_used(used);
+        self.assets.poll_changes(self.time_real);
+        let tasks = self.assets.fetch().await;
         for task in tasks {
             match task {
                 FetchedTask::CreateTexture { handle, data, size } => {
-                    self.with_platform(|p| {
-                        p.create_texture(handle, data, size);
-                    });
+                    self.render.borrow_mut().create_texture(handle, data, size);
                 }
                 FetchedTask::RemoveTexture { handle } => {
-                    self.with_platform(|p| {
-                        p.remove_texture(handle);
-                    });
+                    self.render.borrow_mut().remove_texture(handle);
                 }
                 FetchedTask::CreateFont { handle, font } => {
                     let Ok(cache) = world.get_resource_mut::<TextCache>() else {
@@ -493,7 +1120,34 @@ impl Engine {
 
     pub fn viewport(&self) -> Vec2 {
         let render = self.render.borrow();
-        render.snap_px(self.camera.viewport)
+        render.snap_px(self.camera().viewport)
+    }
+
+    /// Draw in world space, the camera zoom and rotation are applied to draws,
+    /// positions are still relative to the viewport
+    pub fn draw_world<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let camera = self.camera();
+        let transform = camera.is_transformed().then(|| CameraTransform {
+            center: self.view_size() * 0.5,
+            zoom: camera.zoom,
+            rotation: camera.rotation,
+        });
+        let prev = std::mem::replace(&mut self.render.borrow_mut().camera_transform, transform);
+        let r = f(self);
+        self.render.borrow_mut().camera_transform = prev;
+        r
+    }
+
+    /// Convert a world position to the screen position
+    pub fn world_to_screen(&self, pos: Vec2) -> Vec2 {
+        let screen_size = self.render.borrow().logical_size();
+        self.camera().world_to_screen(pos, screen_size)
+    }
+
+    /// Convert a screen position to the world position, e.g. the mouse position
+    pub fn screen_to_world(&self, pos: Vec2) -> Vec2 {
+        let screen_size = self.render.borrow().logical_size();
+        self.camera().screen_to_world(pos, screen_size)
     }
 
     /// Set a scene, the scene swap do not happend instantly, it is happend in engine update
@@ -516,3 +1170,36 @@ impl Engine {
         // Do nothing
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::Engine;
+    use crate::layer::{RenderLayer, BACKGROUND, HUD, WORLD};
+
+    #[test]
+    fn test_layer_camera_zoom() {
+        let mut g = Engine::headless();
+        let camera = &mut g.cameras_mut()[0];
+        camera.viewport = Vec2::new(100.0, 40.0);
+        camera.zoom = 2.0;
+
+        let state = |g: &mut Engine, layer: &str| {
+            g.with_layer(layer, |g| {
+                let render = g.render.borrow();
+                (render.layer_offset, render.camera_transform.map(|t| t.zoom))
+            })
+            .unwrap()
+        };
+        assert_eq!(state(&mut g, WORLD), (Vec2::new(100.0, 40.0), Some(2.0)));
+        // parallax layers move and zoom less than the world
+        g.add_layer(RenderLayer::new(BACKGROUND, -100, 0.5));
+        assert_eq!(
+            state(&mut g, BACKGROUND),
+            (Vec2::new(50.0, 20.0), Some(1.5))
+        );
+        assert_eq!(state(&mut g, HUD), (Vec2::ZERO, None));
+        assert!(g.with_layer("missing", |_g| ()).is_none());
+    }
+}
@@ -8,4 +8,8 @@ pub enum Error {
     NoResource,
     #[error("no component")]
     NoComponent,
+    #[error("component existed {0}")]
+    ComponentExisted(String),
+    #[error("invalid component {0}")]
+    InvalidComponent(String),
 }
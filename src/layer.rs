@@ -0,0 +1,163 @@
+//! Named render layers, draws in a layer are sorted by the layer order and
+//! moved by the camera according to the layer camera factor
+
+use glam::Vec2;
+use hashbrown::HashSet;
+
+use crate::color::{Color, WHITE};
+
+/// Background layer, drawn before the world
+pub const BACKGROUND: &str = "background";
+/// World layer, positions are in world space
+pub const WORLD: &str = "world";
+/// Foreground layer, drawn after the world
+pub const FOREGROUND: &str = "foreground";
+/// HUD layer, positions are in screen space
+pub const HUD: &str = "hud";
+/// Debug layer, drawn on top of everything in screen space
+pub const DEBUG: &str = "debug";
+
+/// A named render layer
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderLayer {
+    pub name: String,
+    /// Draw order, used as `DrawKey::layer` of draws in the layer
+    pub order: i32,
+    /// How much the camera moves and zooms the layer, 0 is screen space and 1 is the full camera,
+    /// values between are parallax. The camera rotation is applied in full to a layer above 0
+    pub camera_factor: f32,
+    /// Multiplied with colors of draws in the layer
+    pub tint: Color,
+    /// Fill the layer with the color before its draws
+    pub clear: Option<Color>,
+}
+
+impl RenderLayer {
+    pub fn new(name: impl Into<String>, order: i32, camera_factor: f32) -> Self {
+        Self {
+            name: name.into(),
+            order,
+            camera_factor,
+            tint: WHITE,
+            clear: None,
+        }
+    }
+
+    pub fn with_tint(mut self, tint: Color) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    pub fn with_clear(mut self, color: Color) -> Self {
+        self.clear = Some(color);
+        self
+    }
+
+    /// Offset of draws in the layer by the camera viewport
+    pub(crate) fn offset(&self, viewport: Vec2) -> Vec2 {
+        viewport * self.camera_factor
+    }
+
+    /// Zoom of the layer by the camera zoom, parallax layers zoom less than the world
+    pub(crate) fn zoom(&self, zoom: f32) -> f32 {
+        1.0 + (zoom - 1.0) * self.camera_factor
+    }
+}
+
+/// Layers of the engine, ordered by `RenderLayer::order`
+#[derive(Debug, Clone)]
+pub(crate) struct RenderLayers {
+    layers: Vec<RenderLayer>,
+    /// Missing layers already warned
+    warned: HashSet<String>,
+}
+
+impl Default for RenderLayers {
+    fn default() -> Self {
+        let mut layers = Self {
+            layers: Vec::new(),
+            warned: HashSet::new(),
+        };
+        layers.insert(RenderLayer::new(BACKGROUND, -100, 1.0));
+        layers.insert(RenderLayer::new(WORLD, 0, 1.0));
+        layers.insert(RenderLayer::new(FOREGROUND, 100, 1.0));
+        layers.insert(RenderLayer::new(HUD, 200, 0.0));
+        layers.insert(RenderLayer::new(DEBUG, 300, 0.0));
+        layers
+    }
+}
+
+impl RenderLayers {
+    /// Insert a layer, replace the layer with the same name
+    pub fn insert(&mut self, layer: RenderLayer) -> Option<RenderLayer> {
+        let prev = self.remove(&layer.name);
+        let index = self.layers.partition_point(|l| l.order <= layer.order);
+        self.layers.insert(index, layer);
+        prev
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<RenderLayer> {
+        let index = self.layers.iter().position(|l| l.name == name)?;
+        Some(self.layers.remove(index))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&RenderLayer> {
+        self.layers.iter().find(|l| l.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RenderLayer> {
+        self.layers.iter()
+    }
+
+    /// Warn a missing layer, each name is warned once
+    pub fn warn_missing(&mut self, name: &str) {
+        if self.warned.insert(name.to_string()) {
+            log::warn!("Can't find render layer {name}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::{RenderLayer, RenderLayers, BACKGROUND, HUD, WORLD};
+
+    #[test]
+    fn test_layers_order() {
+        let mut layers = RenderLayers::default();
+        assert_eq!(layers.get(HUD).unwrap().camera_factor, 0.0);
+
+        // move the world above the hud
+        let prev = layers.insert(RenderLayer::new(WORLD, 250, 1.0));
+        assert_eq!(prev.unwrap().order, 0);
+        let names: Vec<_> = layers.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["background", "foreground", "hud", "world", "debug"]);
+
+        layers.insert(RenderLayer::new("parallax", -100, 0.5));
+        assert_eq!(layers.iter().nth(1).unwrap().name, "parallax");
+        assert!(layers.remove("parallax").is_some());
+        assert!(layers.get("parallax").is_none());
+
+        layers.warn_missing("parallax");
+        layers.warn_missing("parallax");
+        assert_eq!(layers.warned.len(), 1);
+    }
+
+    #[test]
+    fn test_layer_camera() {
+        let viewport = Vec2::new(100.0, 40.0);
+        let world = RenderLayer::new(WORLD, 0, 1.0);
+        assert_eq!(world.offset(viewport), viewport);
+        assert_eq!(world.zoom(2.0), 2.0);
+
+        let parallax = RenderLayer::new(BACKGROUND, -100, 0.5);
+        assert_eq!(parallax.offset(viewport), Vec2::new(50.0, 20.0));
+        assert_eq!(parallax.zoom(2.0), 1.5);
+        assert_eq!(parallax.zoom(0.5), 0.75);
+
+        let hud = RenderLayer::new(HUD, 200, 0.0);
+        assert_eq!(hud.offset(viewport), Vec2::ZERO);
+        assert_eq!(hud.zoom(2.0), 1.0);
+    }
+}
@@ -1,7 +1,9 @@
 pub mod app;
 pub mod asset;
+pub mod atlas;
 pub mod camera;
 pub mod color;
+pub mod draw_queue;
 pub mod ecs;
 pub mod engine;
 pub mod errors;
@@ -9,11 +11,15 @@ pub mod font;
 pub mod handle;
 pub mod health;
 pub mod input;
+pub mod layer;
+pub mod lifetime;
 pub mod map;
+pub mod perf;
 mod platform;
 pub mod prelude;
 mod render;
 pub mod sat;
+mod shape;
 pub mod sprite;
 pub mod text_cache;
 pub mod transform;
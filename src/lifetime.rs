@@ -0,0 +1,140 @@
+use roast2d_derive::{Component, Resource};
+
+use crate::{
+    ecs::{component::ComponentId, entity::Ent, world::World},
+    engine::Engine,
+    transform::Transform,
+};
+
+/// Lifetime
+///
+/// The engine ticks it down with game time and despawns the entity on expiry.
+/// Despawned entities are recorded in `LifetimeEvents`, physics users get `EntHooks::kill` called instead.
+#[derive(Component, Debug, Clone)]
+pub struct Lifetime {
+    /// Remaining seconds
+    pub remaining: f32,
+}
+
+impl Lifetime {
+    pub fn new(remaining: f32) -> Self {
+        Self { remaining }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining <= 0.0
+    }
+}
+
+/// An entity despawned because the lifetime is expired
+#[derive(Debug, Clone)]
+pub struct Expired {
+    pub ent: Ent,
+    /// The last transform of the entity, useful to spawn effects
+    pub transform: Option<Transform>,
+}
+
+/// Entities expired in the last frame, available until the next frame is updated
+///
+/// Entities with physics hooks are killed through `Commands::kill` before the engine
+/// despawns expired entities, they never appear here, handle them in `EntHooks::kill`.
+#[derive(Resource, Default)]
+pub struct LifetimeEvents {
+    expired: Vec<Expired>,
+}
+
+impl LifetimeEvents {
+    pub fn iter(&self) -> impl Iterator<Item = &Expired> {
+        self.expired.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.expired.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expired.is_empty()
+    }
+}
+
+pub(crate) fn init_lifetime(_g: &mut Engine, w: &mut World) {
+    w.add_resource(LifetimeEvents::default());
+}
+
+/// Tick down lifetimes by the engine tick, called before scene update
+pub(crate) fn tick_lifetimes(tick: f32, w: &mut World) {
+    let ents: Vec<_> = w
+        .iter_by_id(&ComponentId::of::<Lifetime>())
+        .cloned()
+        .collect();
+    for ent in ents {
+        if let Ok(lifetime) = w.ent_mut(ent).get_mut::<Lifetime>() {
+            lifetime.remaining -= tick;
+        }
+    }
+}
+
+/// Despawn expired entities, called after scene update
+pub(crate) fn despawn_expired(w: &mut World) {
+    let ents: Vec<_> = w
+        .iter_by_id(&ComponentId::of::<Lifetime>())
+        .cloned()
+        .collect();
+    let mut expired = Vec::new();
+    for ent in ents {
+        let ent_ref = w.ent(ent);
+        if !ent_ref.get::<Lifetime>().is_ok_and(|l| l.is_expired()) {
+            continue;
+        }
+        let transform = ent_ref.get::<Transform>().ok().cloned();
+        expired.push(Expired { ent, transform });
+    }
+    for e in &expired {
+        w.despawn(e.ent);
+    }
+    if let Ok(events) = w.get_resource_mut::<LifetimeEvents>() {
+        events.expired = expired;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::{despawn_expired, tick_lifetimes, Lifetime, LifetimeEvents};
+    use crate::{ecs::world::World, transform::Transform};
+
+    #[test]
+    fn test_lifetime_expire() {
+        let mut w = World::default();
+        w.add_resource(LifetimeEvents::default());
+        let bullet = w
+            .spawn()
+            .add(Lifetime::new(0.5))
+            .add(Transform::new(Vec2::new(1.0, 2.0), Vec2::ONE))
+            .id();
+        let wall = w.spawn().add(Lifetime::new(1.0)).id();
+
+        tick_lifetimes(0.25, &mut w);
+        assert_eq!(
+            w.get(bullet).unwrap().get::<Lifetime>().unwrap().remaining,
+            0.25
+        );
+        despawn_expired(&mut w);
+        assert!(w.get_resource::<LifetimeEvents>().unwrap().is_empty());
+
+        tick_lifetimes(0.25, &mut w);
+        despawn_expired(&mut w);
+        assert!(w.get(bullet).is_err());
+        assert!(w.get(wall).is_ok());
+        let events = w.get_resource::<LifetimeEvents>().unwrap();
+        assert_eq!(events.len(), 1);
+        let expired = events.iter().next().unwrap();
+        assert_eq!(expired.ent, bullet);
+        assert_eq!(expired.transform.as_ref().unwrap().pos, Vec2::new(1.0, 2.0));
+
+        // events are cleared on next frame
+        despawn_expired(&mut w);
+        assert!(w.get_resource::<LifetimeEvents>().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::VecDeque;
+
+/// Default frames kept in the perf history
+const DEFAULT_HISTORY_CAPACITY: usize = 240;
+
+/// Various infos about the last frame
+#[derive(Default, Debug)]
+pub struct Perf {
+    pub entities: usize,
+    pub update: f32,
+    pub draw: f32,
+    pub total: f32,
+    /// Draw calls sent to the platform
+    pub draw_calls: u32,
+    /// Draws rejected because they are out of the screen
+    pub culled_draws: u32,
+    /// Texture switches when flushing draws
+    pub draw_batches: u32,
+    /// Alive textures
+    pub textures: usize,
+    /// Estimated memory of alive textures in bytes, included in `asset_memory`
+    pub texture_memory: usize,
+    /// Cached text textures
+    pub text_cache_size: usize,
+    /// Hit rate of text cache in this frame, 0.0 ~ 1.0
+    pub text_cache_hit_rate: f32,
+    /// Asset tasks waiting to be fetched
+    pub pending_assets: usize,
+    /// Memory held by assets in bytes
+    pub asset_memory: usize,
+    /// Entity pairs checked by collision detection
+    pub physics_pair_checks: u32,
+    /// Seconds spent in systems, measured by `Engine::measure`
+    pub systems: Vec<(&'static str, f32)>,
+    /// Rolling history of recent frames
+    pub history: PerfHistory,
+}
+
+impl Perf {
+    /// Add time of a system, times of the same system are accumulated in a frame
+    pub fn add_system_time(&mut self, name: &'static str, time: f32) {
+        match self.systems.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, t)) => *t += time,
+            None => self.systems.push((name, time)),
+        }
+    }
+
+    /// Get time of a system
+    pub fn system_time(&self, name: &str) -> Option<f32> {
+        self.systems
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, t)| *t)
+    }
+
+    /// Reset per-frame counters
+    pub(crate) fn begin_frame(&mut self) {
+        self.physics_pair_checks = 0;
+        self.systems.clear();
+    }
+
+    /// Record the frame into history
+    pub(crate) fn end_frame(&mut self) {
+        let sample = PerfSample {
+            entities: self.entities,
+            update: self.update,
+            draw: self.draw,
+            total: self.total,
+            draw_calls: self.draw_calls,
+        };
+        self.history.push(sample);
+    }
+}
+
+/// A frame in perf history
+#[derive(Default, Debug, Clone, Copy)]
+pub struct PerfSample {
+    pub entities: usize,
+    pub update: f32,
+    pub draw: f32,
+    pub total: f32,
+    pub draw_calls: u32,
+}
+
+/// Rolling history of frames, the oldest frame is dropped once full
+#[derive(Debug)]
+pub struct PerfHistory {
+    samples: VecDeque<PerfSample>,
+    capacity: usize,
+}
+
+impl Default for PerfHistory {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+impl PerfHistory {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Set capacity, drop oldest frames if needed
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.samples.len() > capacity {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn push(&mut self, sample: PerfSample) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.samples.len() >= self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Iterate frames from oldest to newest
+    pub fn iter(&self) -> impl Iterator<Item = &PerfSample> {
+        self.samples.iter()
+    }
+
+    /// Average total frame time
+    pub fn avg_total(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        self.samples.iter().map(|s| s.total).sum::<f32>() / self.samples.len() as f32
+    }
+
+    /// The slowest frame
+    pub fn max_total(&self) -> f32 {
+        self.samples.iter().map(|s| s.total).fold(0.0, f32::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Perf, PerfHistory, PerfSample};
+
+    #[test]
+    fn test_history_rolling() {
+        let mut history = PerfHistory::with_capacity(3);
+        for i in 0..5 {
+            history.push(PerfSample {
+                total: i as f32,
+                ..Default::default()
+            });
+        }
+        assert_eq!(history.len(), 3);
+        let totals: Vec<_> = history.iter().map(|s| s.total).collect();
+        assert_eq!(totals, vec![2.0, 3.0, 4.0]);
+        assert_eq!(history.max_total(), 4.0);
+        assert_eq!(history.avg_total(), 3.0);
+
+        history.set_capacity(1);
+        assert_eq!(
+            history.iter().map(|s| s.total).collect::<Vec<_>>(),
+            vec![4.0]
+        );
+    }
+
+    #[test]
+    fn test_system_time() {
+        let mut perf = Perf::default();
+        perf.add_system_time("collision", 0.5);
+        perf.add_system_time("collision", 0.25);
+        assert_eq!(perf.system_time("collision"), Some(0.75));
+        perf.begin_frame();
+        assert_eq!(perf.system_time("collision"), None);
+    }
+}
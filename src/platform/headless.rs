@@ -0,0 +1,69 @@
+use std::time::Instant;
+
+use anyhow::{bail, Result};
+use glam::UVec2;
+
+use super::Platform;
+use crate::{
+    app::App,
+    color::Color,
+    ecs::world::World,
+    engine::Engine,
+    handle::{Handle, HandleId},
+    render::BlendMode,
+    types::Rect,
+};
+
+/// Platform without a window, draws are dropped
+pub(crate) struct HeadlessPlatform {
+    start: Instant,
+}
+
+impl HeadlessPlatform {
+    pub(crate) fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Platform for HeadlessPlatform {
+    fn now(&mut self) -> f32 {
+        self.start.elapsed().as_secs_f32()
+    }
+
+    fn prepare_frame(&mut self) {}
+
+    fn end_frame(&mut self) {}
+
+    fn cleanup(&mut self) {}
+
+    fn draw(
+        &mut self,
+        _texture: &Handle,
+        _color: Color,
+        _blend: BlendMode,
+        _src: Option<Rect>,
+        _dst: Rect,
+        _angle: Option<f32>,
+        _flip_x: bool,
+        _flip_y: bool,
+    ) {
+    }
+
+    fn set_clip(&mut self, _clip: Option<Rect>) {}
+
+    fn create_texture(&mut self, _handle: Handle, _data: Vec<u8>, _size: UVec2) {}
+
+    fn remove_texture(&mut self, _handle_id: HandleId) {}
+
+    fn create_render_target(&mut self, _handle: Handle, _size: UVec2) {}
+
+    fn set_render_target(&mut self, _target: Option<&Handle>) {}
+
+    fn clear(&mut self, _color: Color) {}
+
+    async fn run<Setup: FnOnce(&mut Engine, &mut World)>(_app: App, _setup: Setup) -> Result<()> {
+        bail!("Headless platform has no window to run an app, use Engine::headless")
+    }
+}
@@ -7,14 +7,20 @@ use crate::{
     ecs::world::World,
     engine::Engine,
     handle::{Handle, HandleId},
+    render::BlendMode,
     types::Rect,
 };
 
+#[cfg(not(target_arch = "wasm32"))]
+mod headless;
 #[cfg(not(target_arch = "wasm32"))]
 mod sdl;
 #[cfg(target_arch = "wasm32")]
 mod web;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use headless::HeadlessPlatform;
+
 pub trait Platform {
     /// Return seconds since game started
     fn now(&mut self) -> f32;
@@ -25,14 +31,23 @@ pub trait Platform {
         &mut self,
         texture: &Handle,
         color: Color,
+        blend: BlendMode,
         src: Option<Rect>,
         dst: Rect,
         angle: Option<f32>,
         flip_x: bool,
         flip_y: bool,
     );
+    /// Clip following draws to a rect in screen pixels, `None` to disable clipping
+    fn set_clip(&mut self, clip: Option<Rect>);
     fn create_texture(&mut self, handle: Handle, data: Vec<u8>, size: UVec2);
     fn remove_texture(&mut self, handle_id: HandleId);
+    /// Create an offscreen texture that draws can be redirected to
+    fn create_render_target(&mut self, handle: Handle, size: UVec2);
+    /// Redirect following draws to the render target, `None` to draw to the screen
+    fn set_render_target(&mut self, target: Option<&Handle>);
+    /// Clear the current render target with color
+    fn clear(&mut self, color: Color);
     #[allow(async_fn_in_trait)]
     async fn run<Setup: FnOnce(&mut Engine, &mut World)>(app: App, setup: Setup) -> Result<()>
     where
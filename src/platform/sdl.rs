@@ -9,7 +9,7 @@ use sdl2::{
     keyboard::{Mod, Scancode},
     mouse::MouseButton,
     pixels::PixelFormatEnum,
-    render::{Canvas, Texture, TextureCreator},
+    render::{BlendMode as SdlBlendMode, Canvas, Texture, TextureCreator},
     surface::Surface,
     video::{FullscreenType, Window, WindowContext},
     GameControllerSubsystem, Sdl,
@@ -22,6 +22,7 @@ use crate::{
     engine::Engine,
     handle::{Handle, HandleId},
     input::{KeyCode, KeyState},
+    render::BlendMode,
     types::Rect,
 };
 
@@ -57,10 +58,36 @@ impl ScreenBuffer {
     }
 }
 
+// Custom blend modes are not variants of `SDL_BlendMode`, pass them as raw values
+extern "C" {
+    fn SDL_ComposeCustomBlendMode(
+        src_color_factor: u32,
+        dst_color_factor: u32,
+        color_operation: u32,
+        src_alpha_factor: u32,
+        dst_alpha_factor: u32,
+        alpha_operation: u32,
+    ) -> u32;
+    #[link_name = "SDL_SetTextureBlendMode"]
+    fn SDL_SetTextureBlendModeRaw(texture: *mut sdl2::sys::SDL_Texture, blend_mode: u32) -> i32;
+}
+
+/// dstRGBA = srcRGBA + dstRGBA * (1 - srcA)
+fn premultiplied_blend_mode() -> u32 {
+    use sdl2::sys::{SDL_BlendFactor, SDL_BlendOperation};
+    let one = SDL_BlendFactor::SDL_BLENDFACTOR_ONE as u32;
+    let one_minus_src_alpha = SDL_BlendFactor::SDL_BLENDFACTOR_ONE_MINUS_SRC_ALPHA as u32;
+    let add = SDL_BlendOperation::SDL_BLENDOPERATION_ADD as u32;
+    unsafe {
+        SDL_ComposeCustomBlendMode(one, one_minus_src_alpha, add, one, one_minus_src_alpha, add)
+    }
+}
+
 pub struct SDLPlatform {
     screen_buffer: ScreenBuffer,
     textures: HashMap<u64, Texture>,
     start: Instant,
+    premultiplied_blend: u32,
 }
 
 impl SDLPlatform {
@@ -69,6 +96,7 @@ impl SDLPlatform {
             screen_buffer,
             textures: Default::default(),
             start: Instant::now(),
+            premultiplied_blend: premultiplied_blend_mode(),
         }
     }
 }
@@ -92,6 +120,7 @@ impl Platform for SDLPlatform {
         &mut self,
         handle: &Handle,
         color: Color,
+        blend: BlendMode,
         src: Option<Rect>,
         dst: Rect,
         angle: Option<f32>,
@@ -125,6 +154,20 @@ impl Platform for SDLPlatform {
         };
         texture.set_color_mod(color.r, color.g, color.b);
         texture.set_alpha_mod(color.a);
+        match blend {
+            BlendMode::Alpha => texture.set_blend_mode(SdlBlendMode::Blend),
+            BlendMode::Additive => texture.set_blend_mode(SdlBlendMode::Add),
+            BlendMode::Multiply => texture.set_blend_mode(SdlBlendMode::Mul),
+            BlendMode::None => texture.set_blend_mode(SdlBlendMode::None),
+            BlendMode::Premultiplied => {
+                let ret =
+                    unsafe { SDL_SetTextureBlendModeRaw(texture.raw(), self.premultiplied_blend) };
+                if ret != 0 {
+                    log::debug!("Premultiplied blend mode is not supported");
+                    texture.set_blend_mode(SdlBlendMode::Blend);
+                }
+            }
+        }
 
         self.screen_buffer
             .canvas
@@ -132,6 +175,19 @@ impl Platform for SDLPlatform {
             .unwrap();
     }
 
+    fn set_clip(&mut self, clip: Option<Rect>) {
+        let clip = clip.map(|Rect { min, max }| {
+            let size = (max - min).max(Vec2::ZERO);
+            sdl2::rect::Rect::new(
+                min.x.round() as i32,
+                min.y.round() as i32,
+                size.x.round() as u32,
+                size.y.round() as u32,
+            )
+        });
+        self.screen_buffer.canvas.set_clip_rect(clip);
+    }
+
     fn create_texture(&mut self, handle: Handle, mut data: Vec<u8>, size: UVec2) {
         let UVec2 {
             x: width,
@@ -148,7 +204,12 @@ impl Platform for SDLPlatform {
             .create_texture_from_surface(surface)
             .unwrap();
 
-        self.textures.insert(handle.id(), texture);
+        // destroy the previous texture if the handle is reloaded
+        if let Some(prev) = self.textures.insert(handle.id(), texture) {
+            unsafe {
+                prev.destroy();
+            }
+        }
         if self.textures.len() > 4096 {
             log::warn!("Too many textures {}", self.textures.len());
         }
@@ -162,6 +223,49 @@ impl Platform for SDLPlatform {
         }
     }
 
+    fn create_render_target(&mut self, handle: Handle, size: UVec2) {
+        let mut texture = self
+            .screen_buffer
+            .texture_creator
+            .create_texture_target(PixelFormatEnum::RGBA32, size.x, size.y)
+            .unwrap();
+        texture.set_blend_mode(SdlBlendMode::Blend);
+        if let Some(prev) = self.textures.insert(handle.id(), texture) {
+            unsafe {
+                prev.destroy();
+            }
+        }
+    }
+
+    fn set_render_target(&mut self, target: Option<&Handle>) {
+        let texture = match target {
+            Some(handle) => match self.textures.get(&handle.id()) {
+                Some(texture) => texture.raw(),
+                None => {
+                    log::debug!("Failed to get render target {}", handle.id());
+                    return;
+                }
+            },
+            None => std::ptr::null_mut(),
+        };
+        // the safe canvas API only supports render targets in a closure
+        let ret =
+            unsafe { sdl2::sys::SDL_SetRenderTarget(self.screen_buffer.canvas.raw(), texture) };
+        if ret != 0 {
+            log::error!("Failed to set render target: {}", sdl2::get_error());
+        }
+    }
+
+    fn clear(&mut self, color: Color) {
+        let canvas = &mut self.screen_buffer.canvas;
+        let prev = canvas.draw_color();
+        canvas.set_draw_color(sdl2::pixels::Color::RGBA(
+            color.r, color.g, color.b, color.a,
+        ));
+        canvas.clear();
+        canvas.set_draw_color(prev);
+    }
+
     async fn run<Setup: FnOnce(&mut Engine, &mut World)>(app: App, setup: Setup) -> Result<()> {
         let App {
             title,
@@ -203,7 +307,7 @@ impl Platform for SDLPlatform {
             controller_subsystem,
             gamepad: None,
             wants_to_exit: false,
-            inv_window_scale: Vec2::ZERO,
+            window_scale: Vec2::ONE,
         };
 
         event_handler.find_gamepad();
@@ -218,10 +322,7 @@ impl Platform for SDLPlatform {
         // platform_output_samplerate = obtained_spec.freq;
         engine.init(setup);
         engine.on_resize(event_handler.window.drawable_size().into());
-        event_handler.inv_window_scale = {
-            let (x, y) = event_handler.window.size();
-            engine.render.borrow().logical_size() / Vec2::new(x as f32, y as f32)
-        };
+        event_handler.update_window_scale();
 
         while !event_handler.wants_to_exit {
             if let Err(err) = engine.handle_assets().await {
@@ -245,13 +346,20 @@ impl Platform for SDLPlatform {
 struct SDLEventHandler {
     sdl: Sdl,
     window: Window,
-    inv_window_scale: Vec2,
+    // drawable pixels per window point
+    window_scale: Vec2,
     controller_subsystem: GameControllerSubsystem,
     gamepad: Option<GameController>,
     wants_to_exit: bool,
 }
 
 impl SDLEventHandler {
+    fn update_window_scale(&mut self) {
+        let (x, y) = self.window.size();
+        let (dx, dy) = self.window.drawable_size();
+        self.window_scale = Vec2::new(dx as f32 / x as f32, dy as f32 / y as f32);
+    }
+
     fn pump_events(&mut self, engine: &mut Engine) -> Result<()> {
         let mut event_pump = self.sdl.event_pump().map_err(|err| anyhow!(err))?;
         for event in event_pump.poll_iter() {
@@ -382,7 +490,8 @@ impl SDLEventHandler {
                 }
                 Event::MouseMotion { x, y, .. } => {
                     // Mouse move
-                    let mp = Vec2::new(x as f32, y as f32) * self.inv_window_scale;
+                    let pos = Vec2::new(x as f32, y as f32) * self.window_scale;
+                    let mp = engine.render.borrow().screen_to_logical(pos);
                     engine.input.set_mouse_pos(mp);
                 }
                 Event::Quit { .. } => {
@@ -395,10 +504,7 @@ impl SDLEventHandler {
                     ) {
                         engine.on_resize(self.window.drawable_size().into());
 
-                        self.inv_window_scale = {
-                            let (x, y) = self.window.size();
-                            engine.render.borrow().logical_size() / Vec2::new(x as f32, y as f32)
-                        };
+                        self.update_window_scale();
                     }
                 }
                 _ => {}
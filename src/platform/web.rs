@@ -23,6 +23,7 @@ use crate::{
     engine::Engine,
     handle::{Handle, HandleId},
     input::{KeyCode, KeyState},
+    render::BlendMode,
     types::Rect,
 };
 
@@ -97,6 +98,10 @@ pub struct WebPlatform {
     context: CanvasRenderingContext2d,
     buf: CanvasRenderingContext2d,
     textures: HashMap<HandleId, Texture>,
+    // the clip state is saved on the current context
+    clipped: bool,
+    // context of the current render target
+    target: Option<CanvasRenderingContext2d>,
 }
 
 impl WebPlatform {
@@ -118,9 +123,19 @@ impl WebPlatform {
             buffer_canvas,
             buf,
             textures: Default::default(),
+            clipped: false,
+            target: None,
             start,
         }
     }
+
+    /// The context to draw and its pixel ratio, render targets are in texture pixels
+    fn context(&self) -> (&CanvasRenderingContext2d, f64) {
+        match self.target.as_ref() {
+            Some(ctx) => (ctx, 1.0),
+            None => (&self.buf, self.device_pixel_ratio),
+        }
+    }
 }
 
 impl Platform for WebPlatform {
@@ -154,6 +169,7 @@ impl Platform for WebPlatform {
         &mut self,
         texture: &Handle,
         color: Color,
+        blend: BlendMode,
         src: Option<Rect>,
         dst: Rect,
         angle: Option<f32>,
@@ -164,6 +180,7 @@ impl Platform for WebPlatform {
             log::debug!("Can't find image data");
             return;
         };
+        let (buf, ratio) = self.context();
         let canvas = texture.tint_color(color, &self.document);
         let uv_size = match src.as_ref() {
             Some(src) => src.max - src.min,
@@ -184,47 +201,83 @@ impl Platform for WebPlatform {
         if flip_y {
             dy += size.y;
         }
-        self.buf
-            .translate(
-                ((dx as f64).round() * self.device_pixel_ratio).ceil(),
-                ((dy as f64).round() * self.device_pixel_ratio).ceil(),
-            )
-            .unwrap();
-        self.buf
-            .scale(
-                if flip_x { -1.0 } else { 1.0 },
-                if flip_y { -1.0 } else { 1.0 },
-            )
-            .unwrap();
+        buf.translate(
+            ((dx as f64).round() * ratio).ceil(),
+            ((dy as f64).round() * ratio).ceil(),
+        )
+        .unwrap();
+        buf.scale(
+            if flip_x { -1.0 } else { 1.0 },
+            if flip_y { -1.0 } else { 1.0 },
+        )
+        .unwrap();
 
-        let dw = ((size.x as f64).round() * self.device_pixel_ratio).ceil();
-        let dh = ((size.y as f64).round() * self.device_pixel_ratio).ceil();
+        let dw = ((size.x as f64).round() * ratio).ceil();
+        let dh = ((size.y as f64).round() * ratio).ceil();
 
         // rotate by center with angle degree in counter clock-wise
         if let Some(angle) = angle {
             let dw_hf = (dw * 0.5).round();
             let dh_hf = (dh * 0.5).round();
             // move to center
-            self.buf.translate(dw_hf, dh_hf).unwrap();
+            buf.translate(dw_hf, dh_hf).unwrap();
             // rotate counter clockwise
-            self.buf.rotate(-angle as f64).unwrap();
-            self.buf.translate(-dw_hf, -dh_hf).unwrap();
+            buf.rotate(-angle as f64).unwrap();
+            buf.translate(-dw_hf, -dh_hf).unwrap();
+        }
+        let operation = match blend {
+            // canvas has no premultiplied blending
+            BlendMode::Alpha | BlendMode::Premultiplied => None,
+            BlendMode::Additive => Some("lighter"),
+            BlendMode::Multiply => Some("multiply"),
+            BlendMode::None => {
+                // the image is drawn onto transparent pixels
+                buf.clear_rect(0.0, 0.0, dw, dh);
+                None
+            }
+        };
+        if let Some(operation) = operation {
+            buf.set_global_composite_operation(operation).unwrap();
+        }
+        buf.draw_image_with_html_canvas_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+            &canvas,
+            uv_offset.x.round().into(),
+            uv_offset.y.round().into(),
+            uv_size.x.round().into(),
+            uv_size.y.round().into(),
+            0.0,
+            0.0,
+            dw,
+            dh,
+        )
+        .unwrap();
+        if operation.is_some() {
+            buf.set_global_composite_operation("source-over").unwrap();
         }
-        self.buf
-            .draw_image_with_html_canvas_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
-                &canvas,
-                uv_offset.x.round().into(),
-                uv_offset.y.round().into(),
-                uv_size.x.round().into(),
-                uv_size.y.round().into(),
-                0.0,
-                0.0,
-                dw,
-                dh,
-            )
-            .unwrap();
         // clear transform
-        self.buf.reset_transform().unwrap();
+        buf.reset_transform().unwrap();
+    }
+
+    fn set_clip(&mut self, clip: Option<Rect>) {
+        let (buf, ratio) = self.context();
+        let buf = buf.clone();
+        if self.clipped {
+            buf.restore();
+            self.clipped = false;
+        }
+        if let Some(Rect { min, max }) = clip {
+            let size = max - min;
+            buf.save();
+            buf.begin_path();
+            buf.rect(
+                (min.x as f64).round() * ratio,
+                (min.y as f64).round() * ratio,
+                (size.x as f64).round() * ratio,
+                (size.y as f64).round() * ratio,
+            );
+            buf.clip();
+            self.clipped = true;
+        }
     }
 
     fn create_texture(&mut self, handle: Handle, data: Vec<u8>, size: glam::UVec2) {
@@ -253,6 +306,53 @@ impl Platform for WebPlatform {
         self.textures.remove(&handle_id);
     }
 
+    fn create_render_target(&mut self, handle: Handle, size: UVec2) {
+        let canvas: HtmlCanvasElement = self
+            .document
+            .create_element("canvas")
+            .unwrap()
+            .dyn_into()
+            .unwrap();
+        canvas.set_width(size.x);
+        canvas.set_height(size.y);
+        self.textures.insert(handle.id(), Texture { canvas });
+    }
+
+    fn set_render_target(&mut self, target: Option<&Handle>) {
+        self.target = target.and_then(|handle| {
+            let Some(texture) = self.textures.get(&handle.id()) else {
+                log::debug!("Can't find render target {}", handle.id());
+                return None;
+            };
+            texture
+                .canvas
+                .get_context("2d")
+                .unwrap()
+                .unwrap()
+                .dyn_into()
+                .ok()
+        });
+    }
+
+    fn clear(&mut self, color: Color) {
+        let (buf, _ratio) = self.context();
+        let Some(canvas) = buf.canvas() else {
+            return;
+        };
+        let (width, height) = (canvas.width() as f64, canvas.height() as f64);
+        buf.clear_rect(0.0, 0.0, width, height);
+        if color.a > 0 {
+            buf.set_fill_style_str(&format!(
+                "rgba({}, {}, {}, {})",
+                color.r,
+                color.g,
+                color.b,
+                color.a as f64 / 255.0
+            ));
+            buf.fill_rect(0.0, 0.0, width, height);
+        }
+    }
+
     fn now(&mut self) -> f32 {
         let now = self.window.performance().unwrap().now();
         ((now - self.start) / 1000.0) as f32
@@ -563,7 +663,9 @@ fn handle_events(engine: &mut Engine, events_receiver: &mut Receiver<Event>) {
             }
             Event::MouseMove { x, y, .. } => {
                 // Mouse move
-                engine.input.set_mouse_pos(Vec2::new(x as f32, y as f32));
+                let pos = Vec2::new(x as f32, y as f32);
+                let mp = engine.render.borrow().screen_to_logical(pos);
+                engine.input.set_mouse_pos(mp);
             }
         }
     }
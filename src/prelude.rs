@@ -1,8 +1,13 @@
 pub use crate::app::*;
+pub use crate::asset::{AssetLoader, JsonLoader, LoadState};
+pub use crate::atlas::{Atlas, AtlasBuilder};
 pub use crate::color::*;
+pub use crate::draw_queue::DrawKey;
 pub use crate::ecs::component::{Component, ComponentId};
 pub use crate::ecs::entity::Ent;
 pub use crate::ecs::entity_ref::{EntMut, EntRef};
+pub use crate::ecs::name::Name;
+pub use crate::ecs::relation::{OwnedBy, Relation, RidingOn, Targets};
 pub use crate::ecs::resource::Resource;
 pub use crate::ecs::world::World;
 pub use crate::engine::{Engine, Scene};
@@ -11,9 +16,11 @@ pub use crate::font::{Font, Text};
 pub use crate::handle::Handle;
 pub use crate::health::Health;
 pub use crate::input::{ActionId, KeyCode, KeyState};
+pub use crate::layer::RenderLayer;
+pub use crate::lifetime::{Lifetime, LifetimeEvents};
 pub use crate::map::Map;
-pub use crate::render::ScaleMode;
-pub use crate::sprite::Sprite;
+pub use crate::render::{BlendMode, ScaleMode};
+pub use crate::sprite::{NineSlice, SliceMode, Sprite, SpriteFrame, SpriteFrames};
 pub use crate::transform::Transform;
 pub use crate::types::Rect;
 pub use anyhow::{self, Result};
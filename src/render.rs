@@ -1,8 +1,16 @@
 use glam::{UVec2, Vec2};
+use hashbrown::{HashMap, HashSet};
 
 use crate::{
-    color::Color, font::Text, handle::Handle, platform::Platform, sprite::Sprite,
-    text_cache::TextCache, types::Rect,
+    color::{Color, WHITE},
+    draw_queue::{DrawCommand, DrawKey, DrawQueue},
+    font::Text,
+    handle::{Handle, HandleId},
+    platform::Platform,
+    sprite::Sprite,
+    text_cache::TextCache,
+    transform::calc_bounds,
+    types::Rect,
 };
 
 #[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
@@ -15,13 +23,136 @@ pub enum ScaleMode {
     },
     FixedHeight(u32),
     FixedWidth(u32),
+    /// Uniform scale to fit the window, centered with bars
+    Letterbox {
+        width: u32,
+        height: u32,
+    },
+    /// Largest whole multiple that fits the window, centered with bars.
+    /// Keeps pixel art crisp
+    IntegerScale {
+        width: u32,
+        height: u32,
+    },
+}
+
+/// How a draw is blended with the target
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum BlendMode {
+    /// Alpha blending
+    #[default]
+    Alpha,
+    /// Add colors, useful for fire, magic and glow effects
+    Additive,
+    /// Multiply colors, useful for shadows and tinting
+    Multiply,
+    /// Alpha blending of premultiplied colors, same as `Alpha` on web
+    Premultiplied,
+    /// No blending, replace the target pixels
+    None,
+}
+
+/// Zoom and rotation of the camera applied to world-space draws
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CameraTransform {
+    pub center: Vec2,
+    pub zoom: f32,
+    pub rotation: f32,
+}
+
+impl CameraTransform {
+    fn apply(&self, dst: Rect, angle: Option<f32>) -> (Rect, Option<f32>) {
+        let pos = (dst.min + dst.max) * 0.5;
+        let half_size = (dst.max - dst.min) * 0.5 * self.zoom;
+        let pos =
+            self.center + Vec2::from_angle(-self.rotation).rotate(pos - self.center) * self.zoom;
+        let dst = Rect {
+            min: pos - half_size,
+            max: pos + half_size,
+        };
+        let angle = if self.rotation == 0.0 {
+            angle
+        } else {
+            Some(angle.unwrap_or(0.0) - self.rotation)
+        };
+        (dst, angle)
+    }
+
+    /// Map a point on the view back to the space before the transform
+    fn inverse(&self, pos: Vec2) -> Vec2 {
+        self.center + Vec2::from_angle(self.rotation).rotate(pos - self.center) / self.zoom
+    }
+}
+
+/// Return screen scale, logical size and offset of the logical screen
+fn calc_screen_scale(mode: ScaleMode, screen_size: Vec2) -> (Vec2, Vec2, Vec2) {
+    let scale = match mode {
+        ScaleMode::Window => Vec2::splat(1.0),
+        ScaleMode::Fixed { width, height } => screen_size / Vec2::new(width as f32, height as f32),
+        ScaleMode::FixedHeight(height) => Vec2::splat(screen_size.y / height as f32),
+        ScaleMode::FixedWidth(width) => Vec2::splat(screen_size.x / width as f32),
+        ScaleMode::Letterbox { width, height } | ScaleMode::IntegerScale { width, height } => {
+            let logical_size = Vec2::new(width as f32, height as f32);
+            let mut scale = (screen_size / logical_size).min_element();
+            if matches!(mode, ScaleMode::IntegerScale { .. }) {
+                scale = scale.floor().max(1.0);
+            }
+            let offset = ((screen_size - logical_size * scale) * 0.5)
+                .floor()
+                .max(Vec2::ZERO);
+            return (Vec2::splat(scale), logical_size, offset);
+        }
+    };
+    (scale, (screen_size / scale).ceil(), Vec2::ZERO)
+}
+
+/// State of the parent while drawing into a render target
+struct TargetScope {
+    handle: Handle,
+    size: Vec2,
+    clear: Option<Color>,
+    queue: DrawQueue,
+    pane: Option<Rect>,
+    camera_transform: Option<CameraTransform>,
+    layer_offset: Vec2,
+    tint: Color,
+    clips: Vec<Rect>,
+    screen_scale: Vec2,
+    inv_screen_scale: Vec2,
+    screen_offset: Vec2,
 }
 
 /// Render subsystem
 pub(crate) struct Render {
-    draw_calls: u32,
+    pub(crate) draw_calls: u32,
+    pub(crate) culled_draws: u32,
+    /// Texture switches in the last flush
+    pub(crate) draw_batches: u32,
+    /// Key of following draws
+    pub(crate) draw_key: DrawKey,
+    queue: DrawQueue,
+    /// Applied to draws in world space
+    pub(crate) camera_transform: Option<CameraTransform>,
+    /// Camera offset subtracted from draws by the current layer
+    pub(crate) layer_offset: Vec2,
+    /// Tint of the current layer
+    pub(crate) tint: Color,
+    /// Screen rect of the current camera, draws are offset into and clipped by it
+    pub(crate) pane: Option<Rect>,
+    /// Clip rects in view space, the last one is current
+    clips: Vec<Rect>,
+    /// Sizes of render targets
+    render_targets: HashMap<HandleId, Vec2>,
+    /// Render targets being drawn, the last one is current
+    targets: Vec<TargetScope>,
+    /// Alive textures, their memory is tracked by `AssetManager`
+    textures: HashSet<HandleId>,
+    /// Textures drawn since last taken
+    used_textures: HashSet<HandleId>,
     pub(crate) screen_scale: Vec2,
     pub(crate) inv_screen_scale: Vec2,
+    /// Offset of the logical screen in screen pixels, the size of bars
+    pub(crate) screen_offset: Vec2,
     pub(crate) screen_size: Vec2,
     logical_size: Vec2,
     scale_mode: ScaleMode,
@@ -33,8 +164,22 @@ impl Render {
     pub(crate) fn new(platform: Box<dyn Platform + 'static>) -> Self {
         Self {
             draw_calls: 0,
+            culled_draws: 0,
+            draw_batches: 0,
+            draw_key: DrawKey::default(),
+            queue: DrawQueue::default(),
+            camera_transform: None,
+            layer_offset: Vec2::ZERO,
+            tint: WHITE,
+            pane: None,
+            clips: Vec::new(),
+            render_targets: Default::default(),
+            targets: Vec::new(),
+            textures: Default::default(),
+            used_textures: Default::default(),
             screen_scale: Vec2::splat(1.0),
             inv_screen_scale: Vec2::splat(1.0),
+            screen_offset: Vec2::ZERO,
             screen_size: Vec2::default(),
             logical_size: Vec2::default(),
             scale_mode: ScaleMode::default(),
@@ -43,6 +188,156 @@ impl Render {
         }
     }
 
+    /// Reset per-frame counters
+    pub(crate) fn begin_frame(&mut self) {
+        self.draw_calls = 0;
+        self.culled_draws = 0;
+        self.draw_batches = 0;
+        if !self.clips.is_empty() {
+            log::warn!(
+                "{} clips are not popped in the last frame",
+                self.clips.len()
+            );
+            self.clips.clear();
+        }
+    }
+
+    /// Flush sorted draw commands to the platform
+    pub(crate) fn end_frame(&mut self) {
+        let (commands, batches) = self.queue.take_sorted();
+        self.draw_batches += batches;
+        self.flush(commands);
+    }
+
+    fn flush(&mut self, commands: Vec<DrawCommand>) {
+        let mut clip = None;
+        for cmd in commands {
+            if cmd.clip != clip {
+                self.platform.set_clip(cmd.clip.clone());
+                clip = cmd.clip;
+            }
+            self.platform.draw(
+                &cmd.texture,
+                cmd.color,
+                cmd.blend,
+                cmd.src,
+                cmd.dst,
+                cmd.angle,
+                cmd.flip_x,
+                cmd.flip_y,
+            );
+        }
+        if clip.is_some() {
+            self.platform.set_clip(None);
+        }
+    }
+
+    pub(crate) fn set_batch_by_texture(&mut self, enabled: bool) {
+        self.queue.batch_by_texture = enabled;
+    }
+
+    pub(crate) fn create_texture(&mut self, handle: Handle, data: Vec<u8>, size: UVec2) {
+        self.textures.insert(handle.id());
+        self.platform.create_texture(handle, data, size);
+    }
+
+    pub(crate) fn remove_texture(&mut self, handle_id: HandleId) {
+        self.textures.remove(&handle_id);
+        self.render_targets.remove(&handle_id);
+        self.platform.remove_texture(handle_id);
+    }
+
+    /// Clip following draws to rect, the rect is intersected with the current clip
+    pub(crate) fn push_clip(&mut self, rect: Rect) {
+        let rect = match self.clips.last() {
+            Some(clip) => clip.intersect(&rect),
+            None => rect,
+        };
+        self.clips.push(rect);
+    }
+
+    pub(crate) fn pop_clip(&mut self) {
+        if self.clips.pop().is_none() {
+            log::warn!("Pop clip without push");
+        }
+    }
+
+    pub(crate) fn create_render_target(&mut self, handle: Handle, size: UVec2) {
+        self.textures.insert(handle.id());
+        self.render_targets
+            .insert(handle.id(), Vec2::new(size.x as f32, size.y as f32));
+        self.platform.create_render_target(handle, size);
+    }
+
+    /// Redirect following draws to the render target until `end_target`
+    pub(crate) fn begin_target(&mut self, handle: &Handle, clear: Option<Color>) {
+        let size = match self.render_targets.get(&handle.id()) {
+            Some(size) => *size,
+            None => {
+                log::warn!("Handle {} is not a render target", handle.id());
+                Vec2::ZERO
+            }
+        };
+        let mut queue = DrawQueue::default();
+        queue.batch_by_texture = self.queue.batch_by_texture;
+        let scope = TargetScope {
+            handle: handle.clone(),
+            size,
+            clear,
+            queue: std::mem::replace(&mut self.queue, queue),
+            pane: self.pane.take(),
+            camera_transform: self.camera_transform.take(),
+            layer_offset: std::mem::take(&mut self.layer_offset),
+            tint: std::mem::replace(&mut self.tint, WHITE),
+            clips: std::mem::take(&mut self.clips),
+            screen_scale: self.screen_scale,
+            inv_screen_scale: self.inv_screen_scale,
+            screen_offset: self.screen_offset,
+        };
+        // render targets are in texture pixels
+        self.screen_scale = Vec2::splat(1.0);
+        self.inv_screen_scale = Vec2::splat(1.0);
+        self.screen_offset = Vec2::ZERO;
+        self.targets.push(scope);
+    }
+
+    /// Flush draws into the current render target and restore the previous target
+    pub(crate) fn end_target(&mut self) {
+        let Some(scope) = self.targets.pop() else {
+            return;
+        };
+        let (commands, batches) = self.queue.take_sorted();
+        if self.render_targets.contains_key(&scope.handle.id()) {
+            self.draw_batches += batches;
+            self.platform.set_render_target(Some(&scope.handle));
+            if let Some(color) = scope.clear {
+                self.platform.clear(color);
+            }
+            self.flush(commands);
+            let parent = self.targets.last().map(|parent| parent.handle.clone());
+            self.platform.set_render_target(parent.as_ref());
+        }
+        self.queue = scope.queue;
+        self.pane = scope.pane;
+        self.camera_transform = scope.camera_transform;
+        self.layer_offset = scope.layer_offset;
+        self.tint = scope.tint;
+        self.clips = scope.clips;
+        self.screen_scale = scope.screen_scale;
+        self.inv_screen_scale = scope.inv_screen_scale;
+        self.screen_offset = scope.screen_offset;
+    }
+
+    /// Take textures drawn since last call
+    pub(crate) fn take_used_textures(&mut self) -> HashSet<HandleId> {
+        std::mem::take(&mut self.used_textures)
+    }
+
+    /// Alive textures count
+    pub(crate) fn textures_count(&self) -> usize {
+        self.textures.len()
+    }
+
     pub(crate) fn set_default_font(&mut self, handle: Handle) {
         self.default_font.replace(handle);
     }
@@ -56,30 +351,105 @@ impl Render {
         &mut self,
         handle: &Handle,
         color: Color,
+        blend: BlendMode,
         src: Option<Rect>,
         dst: Rect,
         angle: Option<f32>,
         flip_x: bool,
         flip_y: bool,
     ) {
-        if dst.min.x > self.logical_size.x
-            || dst.min.y > self.logical_size.y
-            || dst.max.x < 0.
-            || dst.max.y < 0.
+        let dst = Rect {
+            min: dst.min - self.layer_offset,
+            max: dst.max - self.layer_offset,
+        };
+        let color = if self.tint == WHITE {
+            color
+        } else {
+            color * self.tint
+        };
+        let (dst, angle) = match self.camera_transform {
+            Some(t) => t.apply(dst, angle),
+            None => (dst, angle),
+        };
+        // cull by the bounds of the rotated rect
+        let bounds = match angle {
+            Some(angle) if angle != 0.0 => {
+                calc_bounds((dst.min + dst.max) * 0.5, (dst.max - dst.min) * 0.5, angle)
+            }
+            _ => dst.clone(),
+        };
+        let view_size = self.view_size();
+        if bounds.min.x > view_size.x
+            || bounds.min.y > view_size.y
+            || bounds.max.x < 0.
+            || bounds.max.y < 0.
         {
+            self.culled_draws += 1;
             return;
         }
+        let clip = self.clips.last();
+        if let Some(clip) = clip {
+            if clip.is_empty()
+                || bounds.min.x >= clip.max.x
+                || bounds.min.y >= clip.max.y
+                || bounds.max.x <= clip.min.x
+                || bounds.max.y <= clip.min.y
+            {
+                self.culled_draws += 1;
+                return;
+            }
+        }
+
+        // move into the pane
+        let offset = self.pane.as_ref().map(|pane| pane.min).unwrap_or_default();
+        let dst = Rect {
+            min: dst.min + offset,
+            max: dst.max + offset,
+        };
+        let clip = clip.map(|clip| Rect {
+            min: clip.min + offset,
+            max: clip.max + offset,
+        });
+        let clip = match (self.pane.as_ref(), clip) {
+            (Some(pane), Some(clip)) => Some(pane.intersect(&clip)),
+            (pane, clip) => clip.or_else(|| pane.cloned()),
+        };
+        // keep draws out of bars
+        let clip = if self.is_letterboxed() {
+            let screen = Rect {
+                min: Vec2::ZERO,
+                max: self.logical_size,
+            };
+            Some(clip.map(|clip| clip.intersect(&screen)).unwrap_or(screen))
+        } else {
+            clip
+        };
+        let clip = clip.map(|clip| Rect {
+            min: clip.min * self.screen_scale + self.screen_offset,
+            max: clip.max * self.screen_scale + self.screen_offset,
+        });
 
         // screen scale
         let dst = Rect {
-            min: dst.min * self.screen_scale,
-            max: dst.max * self.screen_scale,
+            min: dst.min * self.screen_scale + self.screen_offset,
+            max: dst.max * self.screen_scale + self.screen_offset,
         };
 
         self.draw_calls += 1;
+        self.used_textures.insert(handle.id());
 
-        self.platform
-            .draw(handle, color, src, dst, angle, flip_x, flip_y);
+        self.queue.push(DrawCommand {
+            key: self.draw_key,
+            texture: handle.clone(),
+            color,
+            blend,
+            src,
+            dst,
+            angle,
+            flip_x,
+            flip_y,
+            clip,
+        });
     }
 
     /// Draw image
@@ -101,6 +471,7 @@ impl Render {
         self.draw(
             &image.texture,
             image.color,
+            image.blend,
             image.src.clone(),
             dst,
             angle,
@@ -150,6 +521,7 @@ impl Render {
         self.draw(
             &image.texture,
             image.color,
+            image.blend,
             Some(src),
             dst,
             angle,
@@ -160,25 +532,25 @@ impl Render {
 
     pub(crate) fn resize(&mut self, size: UVec2) {
         self.screen_size = Vec2::new(size.x as f32, size.y as f32);
-        // calculate scale
-        match self.scale_mode {
-            ScaleMode::Window => {
-                self.screen_scale = Vec2::splat(1.0);
-            }
-            ScaleMode::Fixed { width, height } => {
-                self.screen_scale =
-                    Vec2::new(size.x as f32 / width as f32, size.y as f32 / height as f32);
-            }
-            ScaleMode::FixedHeight(height) => {
-                self.screen_scale = Vec2::splat(size.y as f32 / height as f32);
-            }
-            ScaleMode::FixedWidth(width) => {
-                self.screen_scale = Vec2::splat(size.x as f32 / width as f32);
-            }
-        }
+        let (scale, logical_size, offset) = calc_screen_scale(self.scale_mode, self.screen_size);
+        self.screen_scale = scale;
+        self.inv_screen_scale = 1.0 / scale;
+        self.logical_size = logical_size;
+        self.screen_offset = offset;
+    }
+
+    /// Whether the logical screen is centered with bars
+    fn is_letterboxed(&self) -> bool {
+        self.targets.is_empty()
+            && matches!(
+                self.scale_mode,
+                ScaleMode::Letterbox { .. } | ScaleMode::IntegerScale { .. }
+            )
+    }
 
-        self.logical_size = (self.screen_size / self.screen_scale).ceil();
-        self.inv_screen_scale = 1.0 / self.screen_scale;
+    /// Convert a position in screen pixels to the logical position
+    pub(crate) fn screen_to_logical(&self, pos: Vec2) -> Vec2 {
+        (pos - self.screen_offset) * self.inv_screen_scale
     }
 
     pub(crate) fn create_text_texture(
@@ -193,20 +565,24 @@ impl Render {
             scale,
             color,
         } = text;
+        let default_font = self.default_font.as_ref().expect("no default font").id();
+        let font_id = match font {
+            Some(font) if text_cache.fonts.contains_key(&font.id()) => font.id(),
+            Some(font) => {
+                // the font is loading or failed to load
+                log::debug!("Font {} is not ready, use default font", font.id());
+                default_font
+            }
+            None => default_font,
+        };
         let font = text_cache
-            .get_font(
-                font.as_ref()
-                    .or(self.default_font.as_ref())
-                    .expect("no default font")
-                    .id(),
-            )
+            .get_font(font_id)
             .expect("can't find font by handle id");
         let buffer = font.render_text_texture(text, *scale, *color);
         let width = buffer.width();
         let height = buffer.height();
         let size = UVec2::new(width, height);
-        self.platform
-            .create_texture(handle, buffer.into_vec(), size);
+        self.create_texture(handle, buffer.into_vec(), size);
         size
     }
 
@@ -221,4 +597,76 @@ impl Render {
     pub(crate) fn logical_size(&self) -> Vec2 {
         self.logical_size
     }
+
+    /// Bounds of the view in the space of draw positions, the layer offset and camera are reverted
+    pub(crate) fn visible_rect(&self) -> Rect {
+        let size = self.view_size();
+        let corners = [
+            Vec2::ZERO,
+            Vec2::new(size.x, 0.0),
+            size,
+            Vec2::new(0.0, size.y),
+        ]
+        .map(|p| match self.camera_transform {
+            Some(t) => t.inverse(p),
+            None => p,
+        });
+        let min = corners.into_iter().reduce(Vec2::min).unwrap_or_default();
+        let max = corners.into_iter().reduce(Vec2::max).unwrap_or_default();
+        Rect {
+            min: min + self.layer_offset,
+            max: max + self.layer_offset,
+        }
+    }
+
+    /// Size of a view pixel in the space of draw positions
+    pub(crate) fn pixel_size(&self) -> f32 {
+        self.camera_transform.map_or(1.0, |t| 1.0 / t.zoom)
+    }
+
+    /// Size of the current render target or camera pane, or the logical size
+    pub(crate) fn view_size(&self) -> Vec2 {
+        if let Some(scope) = self.targets.last() {
+            return scope.size;
+        }
+        match self.pane.as_ref() {
+            Some(pane) => pane.max - pane.min,
+            None => self.logical_size,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::{calc_screen_scale, ScaleMode};
+
+    #[test]
+    fn test_letterbox_scale() {
+        let screen_size = Vec2::new(1000.0, 600.0);
+        let mode = ScaleMode::Letterbox {
+            width: 320,
+            height: 240,
+        };
+        let (scale, logical_size, offset) = calc_screen_scale(mode, screen_size);
+        assert_eq!(scale, Vec2::splat(2.5));
+        assert_eq!(logical_size, Vec2::new(320.0, 240.0));
+        assert_eq!(offset, Vec2::new(100.0, 0.0));
+
+        let mode = ScaleMode::IntegerScale {
+            width: 320,
+            height: 240,
+        };
+        let (scale, logical_size, offset) = calc_screen_scale(mode, screen_size);
+        assert_eq!(scale, Vec2::splat(2.0));
+        assert_eq!(logical_size, Vec2::new(320.0, 240.0));
+        assert_eq!(offset, Vec2::new(180.0, 60.0));
+
+        let (scale, logical_size, offset) =
+            calc_screen_scale(ScaleMode::FixedHeight(300), screen_size);
+        assert_eq!(scale, Vec2::splat(2.0));
+        assert_eq!(logical_size, Vec2::new(500.0, 300.0));
+        assert_eq!(offset, Vec2::ZERO);
+    }
 }
@@ -0,0 +1,236 @@
+//! Geometry of primitive shapes, shapes are drawn as textured rects
+
+use std::f32::consts::TAU;
+
+use glam::Vec2;
+
+use crate::types::Rect;
+
+/// Size of the circle texture
+pub(crate) const CIRCLE_TEXTURE_SIZE: u32 = 256;
+
+/// RGBA pixels of a white filled circle, the edge is anti-aliased
+pub(crate) fn circle_pixels(size: u32) -> Vec<u8> {
+    let radius = size as f32 * 0.5;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let d = (Vec2::new(x as f32, y as f32) + 0.5 - radius).length();
+            let alpha = (radius - d + 0.5).clamp(0.0, 1.0);
+            data.extend_from_slice(&[255, 255, 255, (alpha * 255.0).round() as u8]);
+        }
+    }
+    data
+}
+
+/// Rect and angle of a line drawn as a rotated rect,
+/// the line is extended by `cap` at both ends to fill joints
+pub(crate) fn line_rect(from: Vec2, to: Vec2, thickness: f32, cap: f32) -> (Rect, f32) {
+    let d = to - from;
+    let center = (from + to) * 0.5;
+    let half_size = Vec2::new(d.length() * 0.5 + cap, thickness * 0.5);
+    let rect = Rect {
+        min: center - half_size,
+        max: center + half_size,
+    };
+    (rect, d.y.atan2(d.x))
+}
+
+/// Segments to approximate an arc, depend on the radius
+pub(crate) fn arc_segments(radius: f32, arc: f32) -> usize {
+    let full = (radius.max(1.0).sqrt() * 6.0).clamp(12.0, 128.0);
+    ((full * arc.abs() / TAU).ceil() as usize).max(1)
+}
+
+/// Points on an ellipse rotated by `angle`, from `start` to `end` radians
+pub(crate) fn ellipse_points(
+    center: Vec2,
+    radius: Vec2,
+    angle: f32,
+    start: f32,
+    end: f32,
+) -> Vec<Vec2> {
+    let segments = arc_segments(radius.max_element(), end - start);
+    let rot = Vec2::from_angle(angle);
+    (0..=segments)
+        .map(|i| {
+            let t = start + (end - start) * i as f32 / segments as f32;
+            center + rot.rotate(Vec2::from_angle(t) * radius)
+        })
+        .collect()
+}
+
+/// Horizontal spans covering the polygon with the even-odd rule, clipped by `view`,
+/// rows are `step` high, vertically adjacent rows with the same spans are merged
+pub(crate) fn polygon_spans(points: &[Vec2], step: f32, view: &Rect) -> Vec<Rect> {
+    let mut spans: Vec<Rect> = Vec::new();
+    // non-finite points or step never finish the scan
+    if points.len() < 3 || !points.iter().all(|p| p.is_finite()) || !step.is_finite() || step <= 0.0
+    {
+        return spans;
+    }
+    let (min_y, max_y) = points.iter().fold((f32::MAX, f32::MIN), |(min, max), p| {
+        (min.min(p.y), max.max(p.y))
+    });
+    let min_y = min_y.max(view.min.y);
+    let max_y = max_y.min(view.max.y);
+    // spans of the last row, index into `spans`
+    let mut last_row = 0..0;
+    let mut xs = Vec::new();
+    let mut row = Vec::new();
+    let start_y = (min_y / step).floor() * step;
+    // count rows instead of accumulating y, adding the step to a large y may not move it
+    let rows = ((max_y - start_y) / step).ceil().max(0.0) as usize;
+    for i in 0..rows {
+        let y = start_y + i as f32 * step;
+        let sample = y + step * 0.5;
+        xs.clear();
+        for (i, a) in points.iter().enumerate() {
+            let b = points[(i + 1) % points.len()];
+            if (a.y <= sample) != (b.y <= sample) {
+                xs.push(a.x + (sample - a.y) * (b.x - a.x) / (b.y - a.y));
+            }
+        }
+        xs.sort_by(f32::total_cmp);
+        row.clear();
+        row.extend(
+            xs.chunks_exact(2)
+                .map(|x| (x[0].max(view.min.x), x[1].min(view.max.x)))
+                .filter(|(min, max)| min < max),
+        );
+        let row_min = y.max(min_y);
+        let row_max = (y + step).min(max_y);
+        let same_as_last = row.len() == last_row.len()
+            && row.iter().zip(&spans[last_row.clone()]).all(|(x, span)| {
+                (x.0 - span.min.x).abs() < 1e-3 && (x.1 - span.max.x).abs() < 1e-3
+            });
+        if same_as_last {
+            for span in &mut spans[last_row.clone()] {
+                span.max.y = row_max;
+            }
+        } else {
+            let start = spans.len();
+            spans.extend(row.iter().map(|x| Rect {
+                min: Vec2::new(x.0, row_min),
+                max: Vec2::new(x.1, row_max),
+            }));
+            last_row = start..spans.len();
+        }
+    }
+    spans
+}
+
+/// Rect of a corner of the rect, the corner is in the direction of `dir`
+pub(crate) fn corner_rect(rect: &Rect, radius: f32, dir: Vec2) -> Rect {
+    let x = if dir.x < 0.0 {
+        rect.min.x..rect.min.x + radius
+    } else {
+        rect.max.x - radius..rect.max.x
+    };
+    let y = if dir.y < 0.0 {
+        rect.min.y..rect.min.y + radius
+    } else {
+        rect.max.y - radius..rect.max.y
+    };
+    Rect {
+        min: Vec2::new(x.start, y.start),
+        max: Vec2::new(x.end, y.end),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, TAU};
+
+    use glam::Vec2;
+
+    use super::{circle_pixels, ellipse_points, line_rect, polygon_spans};
+    use crate::types::Rect;
+
+    const VIEW: Rect = Rect {
+        min: Vec2::splat(-100.0),
+        max: Vec2::splat(100.0),
+    };
+
+    #[test]
+    fn test_line_rect() {
+        let (rect, angle) = line_rect(Vec2::new(10.0, 10.0), Vec2::new(10.0, 30.0), 2.0, 0.0);
+        assert_eq!(angle, FRAC_PI_2);
+        assert_eq!(rect.min, Vec2::new(0.0, 19.0));
+        assert_eq!(rect.max, Vec2::new(20.0, 21.0));
+    }
+
+    #[test]
+    fn test_ellipse_points() {
+        let points = ellipse_points(Vec2::ZERO, Vec2::new(20.0, 10.0), 0.0, 0.0, TAU);
+        assert!(points.len() > 12);
+        assert!(points[0].abs_diff_eq(Vec2::new(20.0, 0.0), 1e-4));
+        assert!(points.last().unwrap().abs_diff_eq(points[0], 1e-4));
+        for p in points {
+            let d = (p / Vec2::new(20.0, 10.0)).length();
+            assert!((d - 1.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_polygon_spans() {
+        // a rect is merged into one span
+        let rect = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 5.0),
+            Vec2::new(0.0, 5.0),
+        ];
+        let spans = polygon_spans(&rect, 1.0, &VIEW);
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].min, Vec2::ZERO);
+        assert_eq!(spans[0].max, Vec2::new(10.0, 5.0));
+
+        // a concave polygon has two spans in the notch rows
+        let u = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(4.0, 2.0),
+            Vec2::new(4.0, 0.0),
+            Vec2::new(6.0, 0.0),
+            Vec2::new(6.0, 4.0),
+            Vec2::new(0.0, 4.0),
+        ];
+        let spans = polygon_spans(&u, 1.0, &VIEW);
+        assert_eq!(spans.len(), 3);
+        let area: f32 = spans
+            .iter()
+            .map(|s| (s.max.x - s.min.x) * (s.max.y - s.min.y))
+            .sum();
+        assert_eq!(area, 20.0);
+
+        assert!(polygon_spans(&rect[..2], 1.0, &VIEW).is_empty());
+
+        // clipped by the view, stepped by a half pixel
+        let view = Rect {
+            min: Vec2::new(2.0, 1.0),
+            max: Vec2::new(8.0, 3.0),
+        };
+        let spans = polygon_spans(&rect, 0.5, &view);
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].min, view.min);
+        assert_eq!(spans[0].max, view.max);
+
+        // a huge polygon only scans the view
+        let huge = rect.map(|p| p * 1e9);
+        assert_eq!(polygon_spans(&huge, 1.0, &VIEW).len(), 1);
+        // a non-finite point is not drawn
+        let mut bad = rect;
+        bad[2].y = f32::INFINITY;
+        assert!(polygon_spans(&bad, 1.0, &VIEW).is_empty());
+    }
+
+    #[test]
+    fn test_circle_pixels() {
+        let data = circle_pixels(8);
+        let alpha = |x: usize, y: usize| data[(y * 8 + x) * 4 + 3];
+        assert_eq!(alpha(0, 0), 0);
+        assert_eq!(alpha(4, 4), 255);
+    }
+}
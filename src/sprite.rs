@@ -7,6 +7,7 @@ use roast2d_derive::Component;
 use crate::{
     color::{Color, WHITE},
     handle::Handle,
+    render::BlendMode,
     types::Rect,
 };
 
@@ -38,6 +39,10 @@ pub struct Sprite {
     pub flip_y: bool,
     /// Anchor default (0.5, 0.5) is center
     pub anchor: Vec2,
+    /// Border insets, used by `Engine::draw_nine_slice`
+    pub nine_slice: Option<NineSlice>,
+    /// Blend mode
+    pub blend: BlendMode,
 }
 
 impl Sprite {
@@ -53,6 +58,8 @@ impl Sprite {
             flip_x: false,
             flip_y: false,
             anchor: Vec2::splat(0.5),
+            nine_slice: None,
+            blend: BlendMode::Alpha,
         }
     }
 
@@ -62,6 +69,12 @@ impl Sprite {
         Self::new(texture, size)
     }
 
+    /// Set nine-slice border insets
+    pub fn with_nine_slice(mut self, nine_slice: NineSlice) -> Self {
+        self.nine_slice = Some(nine_slice);
+        self
+    }
+
     /// Return image size
     pub fn size(&self) -> UVec2 {
         self.size
@@ -72,3 +85,231 @@ impl Sprite {
         Vec2::new(self.size.x as f32, self.size.y as f32)
     }
 }
+
+/// A frame of animated image
+#[derive(Debug, Clone)]
+pub struct SpriteFrame {
+    /// src rect in the texture
+    pub src: Rect,
+    /// Duration in seconds
+    pub duration: f32,
+}
+
+/// Frames of animated image, loaded by `AssetManager::load_frames`
+#[derive(Debug, Clone)]
+pub struct SpriteFrames {
+    /// Size of each frame
+    pub frame_size: UVec2,
+    pub frames: Vec<SpriteFrame>,
+}
+
+impl SpriteFrames {
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Total duration of all frames in seconds
+    pub fn duration(&self) -> f32 {
+        self.frames.iter().map(|f| f.duration).sum()
+    }
+
+    /// Index of the frame at time, the animation is looped
+    pub fn frame_at(&self, time: f32) -> usize {
+        let duration = self.duration();
+        if duration <= 0.0 {
+            return 0;
+        }
+        let mut time = time.rem_euclid(duration);
+        for (i, frame) in self.frames.iter().enumerate() {
+            if time < frame.duration {
+                return i;
+            }
+            time -= frame.duration;
+        }
+        self.frames.len() - 1
+    }
+
+    /// Build sprite of the frame, `texture` is the handle returned by `load_frames`
+    pub fn sprite(&self, texture: Handle, index: usize) -> Sprite {
+        let mut sprite = Sprite::new(texture, self.frame_size);
+        sprite.src = self.frames.get(index).map(|f| f.src.clone());
+        sprite
+    }
+}
+
+/// How the center and edges of a nine-slice sprite fill the rect
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SliceMode {
+    #[default]
+    Stretch,
+    /// Repeat in texture pixels, the last piece is cropped
+    Tile,
+}
+
+/// Border insets of a nine-slice sprite in texture pixels,
+/// corners keep their size and edges and center fill the rest
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct NineSlice {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub mode: SliceMode,
+}
+
+impl NineSlice {
+    pub fn new(left: f32, top: f32, right: f32, bottom: f32) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+            mode: SliceMode::Stretch,
+        }
+    }
+
+    /// Same insets on all sides
+    pub fn uniform(inset: f32) -> Self {
+        Self::new(inset, inset, inset, inset)
+    }
+
+    pub fn with_mode(mut self, mode: SliceMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Pairs of src and dst rect to draw `src` into `dst`.
+    /// Borders are shrunk if `dst` is smaller than them
+    pub fn patches(&self, src: &Rect, dst: &Rect) -> Vec<(Rect, Rect)> {
+        let tile = self.mode == SliceMode::Tile;
+        let xs = slice_axis(
+            (src.min.x, src.max.x),
+            (self.left, self.right),
+            (dst.min.x, dst.max.x),
+            tile,
+        );
+        let ys = slice_axis(
+            (src.min.y, src.max.y),
+            (self.top, self.bottom),
+            (dst.min.y, dst.max.y),
+            tile,
+        );
+        let mut patches = Vec::with_capacity(xs.len() * ys.len());
+        for (src_y, dst_y) in &ys {
+            for (src_x, dst_x) in &xs {
+                patches.push((
+                    Rect {
+                        min: Vec2::new(src_x.0, src_y.0),
+                        max: Vec2::new(src_x.1, src_y.1),
+                    },
+                    Rect {
+                        min: Vec2::new(dst_x.0, dst_y.0),
+                        max: Vec2::new(dst_x.1, dst_y.1),
+                    },
+                ));
+            }
+        }
+        patches
+    }
+}
+
+type Span = (f32, f32);
+
+/// Slice an axis into pairs of src and dst spans
+fn slice_axis(src: Span, insets: Span, dst: Span, tile: bool) -> Vec<(Span, Span)> {
+    let (a, b) = insets;
+    let dst_len = dst.1 - dst.0;
+    let scale = if a + b > dst_len && a + b > 0.0 {
+        dst_len.max(0.0) / (a + b)
+    } else {
+        1.0
+    };
+    let (da, db) = (a * scale, b * scale);
+    let mut spans = Vec::new();
+    if a > 0.0 {
+        spans.push(((src.0, src.0 + a), (dst.0, dst.0 + da)));
+    }
+    let src_mid = (src.0 + a, src.1 - b);
+    let dst_mid = (dst.0 + da, dst.1 - db);
+    if src_mid.1 > src_mid.0 && dst_mid.1 > dst_mid.0 {
+        if tile {
+            let piece = src_mid.1 - src_mid.0;
+            let mut pos = dst_mid.0;
+            while pos < dst_mid.1 {
+                let len = piece.min(dst_mid.1 - pos);
+                spans.push(((src_mid.0, src_mid.0 + len), (pos, pos + len)));
+                pos += len;
+            }
+        } else {
+            spans.push((src_mid, dst_mid));
+        }
+    }
+    if b > 0.0 {
+        spans.push(((src.1 - b, src.1), (dst.1 - db, dst.1)));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::{NineSlice, SliceMode};
+    use crate::types::Rect;
+
+    fn rect(min: (f32, f32), max: (f32, f32)) -> Rect {
+        Rect {
+            min: Vec2::new(min.0, min.1),
+            max: Vec2::new(max.0, max.1),
+        }
+    }
+
+    #[test]
+    fn test_nine_slice_stretch() {
+        let slice = NineSlice::uniform(4.0);
+        let patches = slice.patches(
+            &rect((0.0, 0.0), (16.0, 16.0)),
+            &rect((10.0, 10.0), (110.0, 50.0)),
+        );
+        assert_eq!(patches.len(), 9);
+        // corners keep the size
+        assert_eq!(patches[0].0, rect((0.0, 0.0), (4.0, 4.0)));
+        assert_eq!(patches[0].1, rect((10.0, 10.0), (14.0, 14.0)));
+        assert_eq!(patches[8].0, rect((12.0, 12.0), (16.0, 16.0)));
+        assert_eq!(patches[8].1, rect((106.0, 46.0), (110.0, 50.0)));
+        // center is stretched
+        assert_eq!(patches[4].0, rect((4.0, 4.0), (12.0, 12.0)));
+        assert_eq!(patches[4].1, rect((14.0, 14.0), (106.0, 46.0)));
+    }
+
+    #[test]
+    fn test_nine_slice_tile() {
+        let slice = NineSlice::new(4.0, 4.0, 4.0, 4.0).with_mode(SliceMode::Tile);
+        // the center is 8px in the texture, 20px wide and 8px high in dst
+        let patches = slice.patches(
+            &rect((0.0, 0.0), (16.0, 16.0)),
+            &rect((0.0, 0.0), (28.0, 16.0)),
+        );
+        assert_eq!(patches.len(), 5 * 3);
+        // the last piece of the top edge is cropped
+        assert_eq!(patches[3].0, rect((4.0, 0.0), (8.0, 4.0)));
+        assert_eq!(patches[3].1, rect((20.0, 0.0), (24.0, 4.0)));
+    }
+
+    #[test]
+    fn test_nine_slice_shrink() {
+        let slice = NineSlice::uniform(4.0);
+        let patches = slice.patches(
+            &rect((0.0, 0.0), (16.0, 16.0)),
+            &rect((0.0, 0.0), (4.0, 4.0)),
+        );
+        // no center, borders are scaled to fit
+        assert_eq!(patches.len(), 4);
+        assert_eq!(patches[0].1, rect((0.0, 0.0), (2.0, 2.0)));
+        assert_eq!(patches[3].1, rect((2.0, 2.0), (4.0, 4.0)));
+    }
+}
@@ -16,6 +16,10 @@ pub struct TextCache {
     pub(crate) fonts: HashMap<u64, Font>,
     pub(crate) cache: HashMap<Text, (Handle, UVec2)>,
     pub(crate) max_text_cache: usize,
+    /// Lookups hit the cache since last reset
+    pub(crate) hits: u32,
+    /// Lookups miss the cache since last reset
+    pub(crate) misses: u32,
 }
 
 impl Default for TextCache {
@@ -24,6 +28,8 @@ impl Default for TextCache {
             fonts: Default::default(),
             cache: Default::default(),
             max_text_cache: 1024,
+            hits: 0,
+            misses: 0,
         }
     }
 }
@@ -42,12 +48,38 @@ impl TextCache {
         self.cache.get(text)
     }
 
+    /// Cached texts count
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Hit rate of lookups since last reset, return 1.0 if no lookups
+    pub fn hit_rate(&self) -> f32 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            return 1.0;
+        }
+        self.hits as f32 / total as f32
+    }
+
+    pub(crate) fn reset_stats(&mut self) {
+        self.hits = 0;
+        self.misses = 0;
+    }
+
     pub fn get_font(&mut self, handle_id: u64) -> Option<&Font> {
         self.fonts.get(&handle_id)
     }
 
     pub fn add_font(&mut self, handle_id: u64, font: Font) {
         self.fonts.insert(handle_id, font);
+        // drop texts rendered with the old font, or with the default font while the font is loading
+        self.cache
+            .retain(|text, _| text.font.as_ref().map(|h| h.id()) != Some(handle_id));
     }
 
     pub fn remove_font(&mut self, handle_id: u64) {
@@ -19,4 +19,44 @@ impl Rect {
         let Rect { min, max } = self;
         pos.x >= min.x && pos.y >= min.y && pos.x <= max.x && pos.y <= max.y
     }
+
+    /// Intersection of rects, the size is zero if they don't overlap
+    pub fn intersect(&self, other: &Self) -> Self {
+        let min = self.min.max(other.min);
+        let max = self.max.min(other.max).max(min);
+        Rect { min, max }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.max.x <= self.min.x || self.max.y <= self.min.y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::Rect;
+
+    #[test]
+    fn test_intersect() {
+        let a = Rect {
+            min: Vec2::ZERO,
+            max: Vec2::splat(10.0),
+        };
+        let b = Rect {
+            min: Vec2::new(5.0, -5.0),
+            max: Vec2::new(20.0, 5.0),
+        };
+        let c = a.intersect(&b);
+        assert_eq!(c.min, Vec2::new(5.0, 0.0));
+        assert_eq!(c.max, Vec2::new(10.0, 5.0));
+        assert!(!c.is_empty());
+
+        let d = Rect {
+            min: Vec2::splat(20.0),
+            max: Vec2::splat(30.0),
+        };
+        assert!(a.intersect(&d).is_empty());
+    }
 }